@@ -1,48 +1,246 @@
+use crate::i18n::Message;
+use crate::priority::{PriorityScale, ScaleError};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Debug)]
+/// 有効な設定。書かれていない項目はデフォルト値になるため、設定ファイル1つ分の層もこの型で検査できる
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Config {
     pub task_management: TaskManagementConfig,
     pub display: DisplayConfig,
     pub file_paths: FilePathsConfig,
+    pub sections: SectionsConfig,
+    pub normalize: NormalizeConfig,
+    /// 優先度の段階（名前と記号、高い順）
+    pub priority: PriorityScale,
+    pub trash: TrashConfig,
+    pub archive: ArchiveConfig,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 pub struct TaskManagementConfig {
     pub default_priority: String,
     pub auto_format: bool,
     pub allow_incomplete_in_archive: bool,
+    /// 他のプロセスがタスクファイルをロックしている場合に待つ秒数
+    pub lock_timeout_secs: u64,
+    /// `add` で同じ本文の未完了タスクがある場合の扱い
+    pub on_duplicate: DuplicatePolicy,
+    /// `add` で作成日（`➕ 2024-12-31`）を記録する
+    pub record_created: bool,
+    /// `done` で完了日（`✅ 2024-12-31`）を記録する
+    pub record_completed: bool,
+}
+
+impl Default for TaskManagementConfig {
+    fn default() -> Self {
+        TaskManagementConfig {
+            default_priority: "medium".to_string(),
+            auto_format: true,
+            allow_incomplete_in_archive: false,
+            lock_timeout_secs: 5,
+            on_duplicate: DuplicatePolicy::default(),
+            record_created: false,
+            record_completed: false,
+        }
+    }
+}
+
+/// `add` で同じ本文の未完了タスクがある場合の扱い（`task_management.on_duplicate`）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// 何もせずに追加する
+    Allow,
+    /// 警告を表示して追加する
+    #[default]
+    Warn,
+    /// 追加しない
+    Refuse,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 pub struct DisplayConfig {
     pub show_completed_by_default: bool,
+    /// メッセージの表示言語（`auto` / `en` / `ja`）。`auto` は `LANG` などから判定する
+    pub locale: String,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        DisplayConfig {
+            show_completed_by_default: false,
+            locale: "auto".to_string(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 pub struct FilePathsConfig {
     pub task_file: String,
 }
 
-impl Default for Config {
+impl Default for FilePathsConfig {
+    fn default() -> Self {
+        FilePathsConfig {
+            task_file: "tasks.md".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct SectionsConfig {
+    /// タスク一覧セクションの見出し（`#` を除いたタイトル）
+    pub task_heading: String,
+    /// アーカイブセクションの見出し（`#` を除いたタイトル）
+    pub archive_heading: String,
+    /// ゴミ箱セクションの見出し（`#` を除いたタイトル）
+    pub trash_heading: String,
+    /// 見出しレベル（`#` の数、1〜6）
+    pub heading_level: usize,
+}
+
+impl Default for SectionsConfig {
+    fn default() -> Self {
+        SectionsConfig {
+            task_heading: "タスク一覧".to_string(),
+            archive_heading: "アーカイブ".to_string(),
+            trash_heading: "ゴミ箱".to_string(),
+            heading_level: 2,
+        }
+    }
+}
+
+/// `remove` で削除したタスクの移動先（`trash.mode`）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashMode {
+    /// タスクファイルのゴミ箱セクション
+    #[default]
+    Section,
+    /// 別のゴミ箱ファイル（`trash.file`）
+    File,
+    /// ゴミ箱を使わずに完全に削除する
+    Delete,
+}
+
+/// ゴミ箱の設定
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct TrashConfig {
+    pub mode: TrashMode,
+    /// `mode = "file"` の場合のゴミ箱ファイル。空ならタスクファイルと同じ場所の `<名前>.trash.md`
+    pub file: String,
+}
+
+/// `archive` でアーカイブしたタスクの移動先（`archive.target`）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveTarget {
+    /// タスクファイルのアーカイブセクション
+    #[default]
+    Section,
+    /// 1つのアーカイブファイル（`archive.file`）
+    File,
+    /// 完了した月ごとのアーカイブファイル（`archive.dir` の `2024-12.md`）
+    Monthly,
+}
+
+/// アーカイブの設定
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    pub target: ArchiveTarget,
+    /// `target = "file"` の場合のアーカイブファイル。空ならタスクファイルと同じ場所の `archive.md`
+    pub file: String,
+    /// `target = "monthly"` の場合のディレクトリ。空ならタスクファイルと同じ場所の `archive`
+    pub dir: String,
+}
+
+/// 正規化ルールごとの有効・無効（キーはルール名）
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct NormalizeConfig {
+    /// 先頭にタスク一覧の見出しを追加する
+    pub task_heading: bool,
+    /// 連続する空行を1行にまとめる
+    pub collapse_blank_lines: bool,
+    /// 優先度記号がないタスクに優先度を追加する
+    pub default_priority: bool,
+    /// アーカイブ内の未完了タスクをタスク一覧に戻す
+    pub unarchive_incomplete: bool,
+    /// タスク一覧を優先度順に並べ替える
+    pub sort_tasks: bool,
+    /// IDのないタスクにIDを付与する
+    pub assign_ids: bool,
+    /// ファイル末尾の改行を確保する
+    pub trailing_newline: bool,
+}
+
+impl Default for NormalizeConfig {
     fn default() -> Self {
-        Config {
-            task_management: TaskManagementConfig {
-                default_priority: "medium".to_string(),
-                auto_format: true,
-                allow_incomplete_in_archive: false,
-            },
-            display: DisplayConfig {
-                show_completed_by_default: false,
-            },
-            file_paths: FilePathsConfig {
-                task_file: "tasks.md".to_string(),
-            },
+        NormalizeConfig {
+            task_heading: true,
+            collapse_blank_lines: true,
+            default_priority: true,
+            unarchive_incomplete: true,
+            sort_tasks: false,
+            assign_ids: true,
+            trailing_newline: true,
+        }
+    }
+}
+
+/// 設定値の出どころ。キーは `task_management.default_priority` の形式、値は設定ファイルのパス
+pub type ConfigSources = BTreeMap<String, String>;
+
+/// どの設定ファイルにも書かれていない値の出どころ
+pub const DEFAULT_SOURCE: &str = "default";
+
+// 設定ファイル1つ分の層（`[section]` ごとのテーブル）を `section.key` 単位で重ね、出どころを記録する
+fn merge_layer(
+    merged: &mut toml::Table,
+    layer: toml::Table,
+    source: &str,
+    sources: &mut ConfigSources,
+) {
+    for (section, value) in layer {
+        let toml::Value::Table(fields) = value else {
+            continue;
+        };
+        let target = merged
+            .entry(section.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        let Some(target) = target.as_table_mut() else {
+            continue;
+        };
+        for (field, value) in fields {
+            sources.insert(format!("{}.{}", section, field), source.to_string());
+            target.insert(field, value);
         }
     }
 }
 
+// デフォルト設定に層を順に重ねて設定を作る。層は読み込み時に型を検査済み
+fn merge_layers(layers: Vec<(toml::Table, String)>) -> (Config, ConfigSources) {
+    let mut merged = match toml::Value::try_from(Config::default()) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    };
+    let mut sources = ConfigSources::new();
+    for (layer, source) in layers {
+        merge_layer(&mut merged, layer, &source, &mut sources);
+    }
+    let config = toml::Value::Table(merged).try_into().unwrap_or_default();
+    (config, sources)
+}
+
 // 設定ディレクトリを取得（開発環境を考慮）
 #[allow(dead_code)]
 pub fn get_config_dir() -> PathBuf {
@@ -62,13 +260,233 @@ pub fn get_config_dir() -> PathBuf {
     }
 }
 
+/// 設定ファイルの問題
+#[derive(Debug)]
+pub enum ConfigError {
+    /// ファイルはあるが読み込めない
+    Read { path: PathBuf, message: String },
+    /// TOMLとして解析できない、または値の型が違う（行・列は1始まり）
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+        /// エラー位置の行の内容
+        source_line: String,
+    },
+    /// 設定にないキー（`section.key` 形式）
+    UnknownKey { path: PathBuf, key: String },
+    /// `task_management.default_priority` が優先度の段階にない（`names` は有効な名前の一覧）
+    InvalidPriority {
+        path: PathBuf,
+        value: String,
+        names: String,
+    },
+    /// `priority.levels` が不正
+    InvalidPriorityScale { path: PathBuf, error: ScaleError },
+}
+
+impl ConfigError {
+    // toml のエラーから位置情報付きのエラーを作る
+    fn parse(path: &Path, contents: &str, error: &toml::de::Error) -> Self {
+        let offset = error
+            .span()
+            .map_or(0, |span| span.start)
+            .min(contents.len());
+        let before = &contents[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ConfigError::Parse {
+            path: path.to_path_buf(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: error.message().to_string(),
+            source_line: contents[line_start..]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Read { path, message } => write!(
+                f,
+                "{}",
+                Message::ConfigReadError {
+                    path: &path.display().to_string(),
+                    error: message,
+                }
+            ),
+            ConfigError::Parse {
+                path,
+                line,
+                column,
+                message,
+                source_line,
+            } => {
+                writeln!(
+                    f,
+                    "{}",
+                    Message::ConfigParseError {
+                        path: &path.display().to_string(),
+                        line: *line,
+                        column: *column,
+                        message,
+                    }
+                )?;
+                // エラー位置の行と、列を指す `^` を表示する
+                let gutter = " ".repeat(line.to_string().len());
+                writeln!(f, "{} |", gutter)?;
+                writeln!(f, "{} | {}", line, source_line)?;
+                write!(f, "{} | {}^", gutter, " ".repeat(column - 1))
+            }
+            ConfigError::UnknownKey { path, key } => write!(
+                f,
+                "{}",
+                Message::UnknownConfigKey {
+                    path: &path.display().to_string(),
+                    key,
+                }
+            ),
+            ConfigError::InvalidPriority { path, value, names } => write!(
+                f,
+                "{}",
+                Message::InvalidConfigPriority {
+                    path: &path.display().to_string(),
+                    value,
+                    names,
+                }
+            ),
+            ConfigError::InvalidPriorityScale { path, error } => {
+                let path = &path.display().to_string();
+                let message = match error {
+                    ScaleError::Empty => Message::EmptyPriorityScale(path),
+                    ScaleError::EmptyField => Message::EmptyPriorityField(path),
+                    ScaleError::Duplicate(value) => Message::DuplicatePriority { path, value },
+                };
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// ファイルの内容を読み込む。ファイルがなければ `None`
+fn read_config_file(path: &Path) -> Result<Option<String>, ConfigError> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(ConfigError::Read {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        }),
+    }
+}
+
+// 設定ファイルを層として読み込む。値の型はファイルごとに検査し、エラーの位置を報告する
 #[allow(dead_code)]
-fn load_config_from_file(path: &PathBuf) -> Option<Config> {
-    if let Ok(contents) = std::fs::read_to_string(path) {
-        toml::from_str(&contents).ok()
-    } else {
-        None
+fn load_config_from_file(path: &Path) -> Result<Option<toml::Table>, ConfigError> {
+    let Some(contents) = read_config_file(path)? else {
+        return Ok(None);
+    };
+    toml::from_str::<Config>(&contents)
+        .and_then(|_| toml::from_str(&contents))
+        .map(Some)
+        .map_err(|e| ConfigError::parse(path, &contents, &e))
+}
+
+/// 設定ファイル1つを検査し、見つかった問題を全て返す（ファイルがなければ空）
+///
+/// 解析エラーに加えて、未知のキーと不正な優先度の段階を報告する。`default_priority` は
+/// 同じファイルで `priority.levels` を指定している場合だけ検査する（他のファイルの段階を
+/// 使う場合があるため。重ねた後の設定は `check_default_priority` で検査する）。
+#[allow(dead_code)]
+pub fn validate_config_file(path: &Path) -> Vec<ConfigError> {
+    let contents = match read_config_file(path) {
+        Ok(Some(contents)) => contents,
+        Ok(None) => return Vec::new(),
+        Err(e) => return vec![e],
+    };
+    let (layer, table) = match toml::from_str::<Config>(&contents)
+        .and_then(|layer| Ok((layer, toml::from_str::<toml::Table>(&contents)?)))
+    {
+        Ok(parsed) => parsed,
+        Err(e) => return vec![ConfigError::parse(path, &contents, &e)],
+    };
+    let has_key = |section: &str, field: &str| {
+        table
+            .get(section)
+            .and_then(|fields| fields.get(field))
+            .is_some()
+    };
+
+    let mut errors = Vec::new();
+
+    // デフォルト設定にないセクション・キーを未知のキーとして報告
+    let known = toml::Value::try_from(Config::default()).unwrap_or(toml::Value::Boolean(false));
+    for (section, value) in &table {
+        let Some(known_section) = known.get(section) else {
+            errors.push(ConfigError::UnknownKey {
+                path: path.to_path_buf(),
+                key: section.clone(),
+            });
+            continue;
+        };
+        let Some(fields) = value.as_table() else {
+            continue;
+        };
+        for field in fields.keys() {
+            if known_section.get(field).is_none() {
+                errors.push(ConfigError::UnknownKey {
+                    path: path.to_path_buf(),
+                    key: format!("{}.{}", section, field),
+                });
+            }
+        }
+    }
+
+    if has_key("priority", "levels") {
+        let scale = layer.priority;
+        let priority = layer.task_management.default_priority;
+        if let Err(error) = scale.validate() {
+            errors.push(ConfigError::InvalidPriorityScale {
+                path: path.to_path_buf(),
+                error,
+            });
+        } else if has_key("task_management", "default_priority") && scale.level(&priority).is_none()
+        {
+            errors.push(ConfigError::InvalidPriority {
+                path: path.to_path_buf(),
+                value: priority,
+                names: scale.names(),
+            });
+        }
     }
+
+    errors
+}
+
+/// 重ねた後の設定で `default_priority` が優先度の段階にあるかを検査する
+///
+/// エラーのパスは `default_priority` を設定したファイル（デフォルト値なら段階を設定したファイル）。
+pub fn check_default_priority(config: &Config, sources: &ConfigSources) -> Option<ConfigError> {
+    let priority = &config.task_management.default_priority;
+    if config.priority.level(priority).is_some() {
+        return None;
+    }
+    let path = sources
+        .get("task_management.default_priority")
+        .or_else(|| sources.get("priority.levels"))
+        .map_or(DEFAULT_SOURCE, String::as_str);
+    Some(ConfigError::InvalidPriority {
+        path: PathBuf::from(path),
+        value: priority.clone(),
+        names: config.priority.names(),
+    })
 }
 
 #[allow(dead_code)]
@@ -115,40 +533,73 @@ fn is_verbose() -> bool {
     std::env::var("MD_TASK_VERBOSE").is_ok() || std::env::var("MD_TASK_DEBUG").is_ok()
 }
 
+// 厳格モードでは設定ファイルに問題があれば読み込みを中止する
 #[allow(dead_code)]
-pub fn load_config() -> Config {
-    let mut config = Config::default();
-    let search_paths = get_config_search_paths();
+fn is_strict() -> bool {
+    std::env::var("MD_TASK_STRICT_CONFIG").is_ok()
+}
+
+/// 設定ファイルを `paths` の順に読み込み、デフォルト設定に項目単位で重ねる（後のファイルが優先）
+///
+/// 有効な設定と、各項目がどのファイルから来たかを返す。
+/// 解析できないファイルは警告を表示して読み飛ばす。厳格モード（`MD_TASK_STRICT_CONFIG`）では
+/// 未知のキーや無効な値も含め、最初に見つかった問題をエラーとして返す。
+#[allow(dead_code)]
+pub fn load_layered_config(paths: &[PathBuf]) -> Result<(Config, ConfigSources), ConfigError> {
+    let mut layers = Vec::new();
+    for path in paths {
+        if is_strict()
+            && let Some(error) = validate_config_file(path).into_iter().next()
+        {
+            return Err(error);
+        }
 
-    // 設定ファイルを優先順で検索・読み込み（グローバル→プロジェクト→ローカルの順で上書き）
-    for path in search_paths.iter().rev() {
-        // 逆順で読み込み（後で上書き）
-        if let Some(loaded_config) = load_config_from_file(path) {
-            config = loaded_config;
+        let layer = match load_config_from_file(path) {
+            Ok(layer) => layer,
+            Err(error) => {
+                eprintln!("{}", error);
+                eprintln!("{}", Message::ConfigSkipped(&path.display().to_string()));
+                None
+            }
+        };
+        if let Some(layer) = layer {
+            layers.push((layer, path.display().to_string()));
             if is_verbose() {
-                println!("Loaded config from: {}", path.display());
+                println!("{}", Message::LoadedConfig(&path.display().to_string()));
             }
         }
     }
 
-    // ローカル設定があれば最終的に上書き
-    let local_config_path = PathBuf::from("md-task.toml");
-    if let Some(local_config) = load_config_from_file(&local_config_path) {
-        merge_configs(&mut config, local_config);
-        if is_verbose() {
-            println!("Applied local config overrides");
+    let (mut config, mut sources) = merge_layers(layers);
+
+    // 優先度の段階が不正ならデフォルトの段階に戻す（厳格モードではファイルの検査で中止済み）
+    if let Err(error) = config.priority.validate() {
+        let path = sources.remove("priority.levels").unwrap_or_default();
+        eprintln!(
+            "{}",
+            ConfigError::InvalidPriorityScale {
+                path: PathBuf::from(path),
+                error,
+            }
+        );
+        config.priority = PriorityScale::default();
+    }
+    if let Some(error) = check_default_priority(&config, &sources) {
+        if is_strict() {
+            return Err(error);
         }
+        eprintln!("{}", error);
     }
 
-    config
+    Ok((config, sources))
 }
 
 #[allow(dead_code)]
-fn merge_configs(base: &mut Config, override_config: Config) {
-    // 各フィールドをローカル設定で上書き
-    base.task_management = override_config.task_management;
-    base.display = override_config.display;
-    base.file_paths = override_config.file_paths;
+pub fn load_config() -> Result<Config, ConfigError> {
+    // グローバル→プロジェクト→ローカルの順で上書き
+    let mut search_paths = get_config_search_paths();
+    search_paths.reverse();
+    Ok(load_layered_config(&search_paths)?.0)
 }
 
 #[allow(dead_code)]
@@ -156,7 +607,7 @@ pub fn create_local_config() -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::default();
     let toml_string = toml::to_string_pretty(&config)?;
     std::fs::write("md-task.toml", toml_string)?;
-    println!("Created local config file: md-task.toml");
+    println!("{}", Message::CreatedLocalConfig("md-task.toml"));
     Ok(())
 }
 
@@ -173,11 +624,14 @@ pub fn install_global_config() -> Result<(), Box<dyn std::error::Error>> {
         let default_config = Config::default();
         let toml_string = toml::to_string_pretty(&default_config)?;
         std::fs::write(&config_file, toml_string)?;
-        println!("Created global config file: {}", config_file.display());
+        println!(
+            "{}",
+            Message::CreatedGlobalConfig(&config_file.display().to_string())
+        );
     } else {
         println!(
-            "Global config file already exists: {}",
-            config_file.display()
+            "{}",
+            Message::GlobalConfigExists(&config_file.display().to_string())
         );
     }
 
@@ -188,65 +642,180 @@ pub fn install_global_config() -> Result<(), Box<dyn std::error::Error>> {
 pub fn show_config_paths() {
     let search_paths = get_config_search_paths();
 
-    println!("Configuration file search order:");
+    println!("{}", Message::ConfigSearchOrder);
     for (i, path) in search_paths.iter().enumerate() {
-        let exists = path.exists();
-        println!(
-            "  {}. {} {}",
-            i + 1,
-            path.display(),
-            if exists { "(exists)" } else { "(not found)" }
-        );
+        let exists = if path.exists() {
+            Message::Exists
+        } else {
+            Message::NotFound
+        };
+        println!("  {}. {} ({})", i + 1, path.display(), exists);
     }
 
-    println!("\nEnvironment variables:");
+    println!("\n{}", Message::EnvironmentVariables);
     println!(
         "  MD_TASK_DEV: {}",
-        std::env::var("MD_TASK_DEV").unwrap_or_else(|_| "not set".to_string())
+        std::env::var("MD_TASK_DEV").unwrap_or_else(|_| Message::NotSet.to_string())
     );
 
-    if let Some(project_root) = find_project_root() {
-        println!("  Project root: {}", project_root.display());
-    } else {
-        println!("  Project root: not detected");
+    let project_root = match find_project_root() {
+        Some(project_root) => project_root.display().to_string(),
+        None => Message::NotDetected.to_string(),
+    };
+    println!("  {}: {}", Message::ProjectRoot, project_root);
+}
+
+/// 設定ファイル1件の状態
+#[derive(Serialize, Debug)]
+pub struct ConfigFileStatus {
+    pub path: String,
+    pub exists: bool,
+}
+
+/// `config status` で表示する内容
+#[derive(Serialize, Debug)]
+pub struct ConfigStatus {
+    pub search_paths: Vec<ConfigFileStatus>,
+    pub md_task_dev: Option<String>,
+    pub project_root: Option<String>,
+    pub values: Vec<ConfigValueStatus>,
+}
+
+/// 有効な設定値1件と、その値を設定したファイル（どのファイルにもなければ `default`）
+#[derive(Serialize, Debug)]
+pub struct ConfigValueStatus {
+    pub key: String,
+    pub value: String,
+    pub source: String,
+}
+
+// 有効な設定を `section.key` ごとの値に展開する
+fn config_values(config: &Config, sources: &ConfigSources) -> Vec<ConfigValueStatus> {
+    let mut values = Vec::new();
+    let Ok(toml::Value::Table(sections)) = toml::Value::try_from(config) else {
+        return values;
+    };
+    for (section, table) in sections {
+        let toml::Value::Table(table) = table else {
+            continue;
+        };
+        for (field, value) in table {
+            let key = format!("{}.{}", section, field);
+            let source = sources
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_SOURCE.to_string());
+            values.push(ConfigValueStatus {
+                key,
+                value: value.to_string(),
+                source,
+            });
+        }
     }
+    values
 }
 
 #[allow(dead_code)]
-pub fn show_config_status() {
-    let search_paths = get_config_search_paths();
+pub fn config_status() -> Result<ConfigStatus, ConfigError> {
+    let search_paths = get_config_search_paths()
+        .iter()
+        .map(|path| ConfigFileStatus {
+            path: path.display().to_string(),
+            exists: path.exists(),
+        })
+        .collect();
+
+    // 現在の有効な設定と、各値を設定したファイル
+    let mut layers = get_config_search_paths();
+    layers.reverse();
+    let (config, sources) = load_layered_config(&layers)?;
+
+    Ok(ConfigStatus {
+        search_paths,
+        md_task_dev: std::env::var("MD_TASK_DEV").ok(),
+        project_root: find_project_root().map(|root| root.display().to_string()),
+        values: config_values(&config, &sources),
+    })
+}
 
-    println!("=== md-task Configuration Status ===");
-    println!("Configuration file search order:");
-    for (i, path) in search_paths.iter().enumerate() {
-        let exists = path.exists();
-        let status = if exists {
-            "✅ exists"
+#[allow(dead_code)]
+pub fn show_config_status() -> Result<(), ConfigError> {
+    let status = config_status()?;
+
+    println!("{}", Message::ConfigStatusTitle);
+    println!("{}", Message::ConfigSearchOrder);
+    for (i, file) in status.search_paths.iter().enumerate() {
+        let exists = if file.exists {
+            format!("✅ {}", Message::Exists)
         } else {
-            "❌ not found"
+            format!("❌ {}", Message::NotFound)
         };
-        println!("  {}. {} {}", i + 1, path.display(), status);
+        println!("  {}. {} {}", i + 1, file.path, exists);
     }
 
-    println!("\nEnvironment:");
+    println!("\n{}", Message::Environment);
     println!(
         "  MD_TASK_DEV: {}",
-        std::env::var("MD_TASK_DEV").unwrap_or_else(|_| "not set".to_string())
+        status
+            .md_task_dev
+            .unwrap_or_else(|| Message::NotSet.to_string())
+    );
+    println!(
+        "  {}: {}",
+        Message::ProjectRoot,
+        status
+            .project_root
+            .unwrap_or_else(|| Message::NotDetected.to_string())
     );
 
-    if let Some(project_root) = find_project_root() {
-        println!("  Project root: {}", project_root.display());
-    } else {
-        println!("  Project root: not detected");
+    println!("\n{}", Message::ActiveConfig);
+    for value in &status.values {
+        let source = if value.source == DEFAULT_SOURCE {
+            Message::DefaultSource.to_string()
+        } else {
+            value.source.clone()
+        };
+        println!("  {} = {} ({})", value.key, value.value, source);
     }
+    Ok(())
+}
 
-    // 現在の有効な設定を表示
-    let config = load_config();
-    println!("\nCurrent active configuration:");
-    println!("  Task file: {}", config.file_paths.task_file);
-    println!(
-        "  Default priority: {}",
-        config.task_management.default_priority
-    );
-    println!("  Auto format: {}", config.task_management.auto_format);
+/// 検索対象の全ての設定ファイルを検査して結果を表示し、見つかった問題の数を返す
+#[allow(dead_code)]
+pub fn validate_config_files() -> usize {
+    let mut problems = 0;
+    for path in get_config_search_paths() {
+        if !path.exists() {
+            println!("  {} ({})", path.display(), Message::NotFound);
+            continue;
+        }
+
+        let errors = validate_config_file(&path);
+        if errors.is_empty() {
+            println!("✅ {}", Message::ConfigValid(&path.display().to_string()));
+        }
+        for error in &errors {
+            println!("{}", error);
+        }
+        problems += errors.len();
+    }
+
+    // 全ての設定ファイルを重ねた後の default_priority
+    let mut layers = get_config_search_paths();
+    layers.reverse();
+    let layers = layers
+        .iter()
+        .filter_map(|path| match load_config_from_file(path) {
+            Ok(Some(layer)) => Some((layer, path.display().to_string())),
+            _ => None,
+        })
+        .collect();
+    let (config, sources) = merge_layers(layers);
+    if config.priority.validate().is_ok()
+        && let Some(error) = check_default_priority(&config, &sources)
+    {
+        println!("{}", error);
+        problems += 1;
+    }
+    problems
 }
@@ -0,0 +1,125 @@
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
+
+/// タスクファイルに書き込む日付の形式
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 今日の日付（ローカルタイムゾーン）
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    match name {
+        "mon" | "monday" | "月" | "月曜" | "月曜日" => Some(Weekday::Mon),
+        "tue" | "tuesday" | "火" | "火曜" | "火曜日" => Some(Weekday::Tue),
+        "wed" | "wednesday" | "水" | "水曜" | "水曜日" => Some(Weekday::Wed),
+        "thu" | "thursday" | "木" | "木曜" | "木曜日" => Some(Weekday::Thu),
+        "fri" | "friday" | "金" | "金曜" | "金曜日" => Some(Weekday::Fri),
+        "sat" | "saturday" | "土" | "土曜" | "土曜日" => Some(Weekday::Sat),
+        "sun" | "sunday" | "日" | "日曜" | "日曜日" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// 今日より後で最初に来る指定曜日
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday() - 1) % 7 + 1;
+    today + Days::new(days_ahead as u64)
+}
+
+// `+3d` / `+2w` 形式の相対日付
+fn parse_offset(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let rest = input.strip_prefix('+')?;
+    let (number, unit_days) = if let Some(n) = rest.strip_suffix('d') {
+        (n, 1)
+    } else if let Some(n) = rest.strip_suffix('w') {
+        (n, 7)
+    } else {
+        (rest, 1)
+    };
+    let number: u64 = number.parse().ok()?;
+    today.checked_add_days(Days::new(number.checked_mul(unit_days)?))
+}
+
+/// 日付の入力を絶対日付に変換する
+///
+/// `2024-12-31` のほか、`today` / `tomorrow` / `+3d` / `+2w` / `next-mon` と
+/// `今日` / `明日` / `明後日` / `3日後` / `来週月曜` のような相対指定を受け付ける。
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let normalized = input.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&normalized, DATE_FORMAT) {
+        return Ok(date);
+    }
+
+    let date = match normalized.as_str() {
+        "today" | "今日" => Some(today),
+        "tomorrow" | "明日" => today.checked_add_days(Days::new(1)),
+        "明後日" => today.checked_add_days(Days::new(2)),
+        "yesterday" | "昨日" => today.checked_sub_days(Days::new(1)),
+        other => {
+            if let Some(days) = other.strip_suffix("日後") {
+                days.parse()
+                    .ok()
+                    .and_then(|days| today.checked_add_days(Days::new(days)))
+            } else if let Some(name) = other
+                .strip_prefix("next-")
+                .or_else(|| other.strip_prefix("来週"))
+            {
+                parse_weekday(name).map(|weekday| next_weekday(today, weekday))
+            } else if other.starts_with('+') {
+                parse_offset(other, today)
+            } else {
+                parse_weekday(other).map(|weekday| next_weekday(today, weekday))
+            }
+        }
+    };
+
+    date.ok_or_else(|| {
+        format!(
+            "Invalid date '{}'. Use YYYY-MM-DD, today, tomorrow, +3d, +2w or next-mon",
+            input
+        )
+    })
+}
+
+/// `30d` / `2w` / `30日` 形式の期間を日数に変換する（単位を省略した場合は日数）
+pub fn parse_days(input: &str) -> Result<u64, String> {
+    let normalized = input.trim().to_lowercase();
+    let (number, unit_days) = if let Some(n) = normalized.strip_suffix('d') {
+        (n, 1)
+    } else if let Some(n) = normalized.strip_suffix('w') {
+        (n, 7)
+    } else if let Some(n) = normalized.strip_suffix('日') {
+        (n, 1)
+    } else if let Some(n) = normalized.strip_suffix("週間") {
+        (n, 7)
+    } else {
+        (normalized.as_str(), 1)
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(unit_days))
+        .ok_or_else(|| format!("Invalid period '{}'. Use 30d, 2w or 30", input))
+}
+
+/// 期日と今日の関係
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DueStatus {
+    /// 期日を過ぎた日数
+    Overdue(i64),
+    Today,
+    /// 期日までの日数
+    Upcoming(i64),
+}
+
+pub fn due_status(due: NaiveDate, today: NaiveDate) -> DueStatus {
+    let days = (due - today).num_days();
+    match days {
+        d if d < 0 => DueStatus::Overdue(-d),
+        0 => DueStatus::Today,
+        d => DueStatus::Upcoming(d),
+    }
+}
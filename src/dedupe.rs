@@ -0,0 +1,126 @@
+use crate::document::{CREATED_SYMBOL, Node, TaskDocument, TaskItem, TaskLocation};
+use unicode_normalization::UnicodeNormalization;
+
+/// 重複の判定に使う本文。Unicode 正規化（NFKC）し、小文字にして空白を取り除く
+///
+/// 優先度記号とIDは本文に含まれないため、優先度だけが違うタスクも重複になる。
+/// 作成日も比較しない。
+pub fn duplicate_key(task: &TaskItem) -> String {
+    let mut task = task.clone();
+    task.set_date_field(CREATED_SYMBOL, None);
+    task.text()
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// 重複の判定の対象。タスク一覧の最上位の未完了タスク（サブタスクは親が違えば別のタスクとみなす）
+pub fn is_candidate(document: &TaskDocument, location: &TaskLocation) -> bool {
+    location.depth == 0
+        && !document.is_archive(location.section)
+        && !document.task(location).is_closed()
+}
+
+/// `task` と同じ本文の未完了タスク
+pub fn find_duplicate(document: &TaskDocument, task: &TaskItem) -> Option<TaskLocation> {
+    let key = duplicate_key(task);
+    document
+        .tasks()
+        .into_iter()
+        .filter(|location| is_candidate(document, location))
+        .find(|location| duplicate_key(document.task(location)) == key)
+}
+
+/// 重複したタスクのまとまり
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// 残すタスク。優先度が最も高いもの（同じならファイル内で先のもの）
+    pub keep: TaskLocation,
+    /// 統合して取り除くタスク
+    pub merge: Vec<TaskLocation>,
+    /// 本文が完全に一致する（`false` なら空白や全角・半角などの違いだけ）
+    pub exact: bool,
+}
+
+/// 重複したタスクのまとまりをファイル内の順に返す
+pub fn find_duplicates(document: &TaskDocument) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<(String, Vec<TaskLocation>)> = Vec::new();
+    for location in document.tasks() {
+        if !is_candidate(document, &location) {
+            continue;
+        }
+        let key = duplicate_key(document.task(&location));
+        match groups.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, locations)) => locations.push(location),
+            None => groups.push((key, vec![location])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, locations)| locations.len() > 1)
+        .map(|(_, mut locations)| {
+            let text = document.task(&locations[0]).text();
+            let exact = locations
+                .iter()
+                .all(|location| document.task(location).text() == text);
+            // 優先度のないタスクは最も低いものとして扱う
+            let keep_index = locations
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, location)| {
+                    document
+                        .task(location)
+                        .priority_level()
+                        .unwrap_or(usize::MAX)
+                })
+                .map_or(0, |(index, _)| index);
+            let keep = locations.remove(keep_index);
+            DuplicateGroup {
+                keep,
+                merge: locations,
+                exact,
+            }
+        })
+        .collect()
+}
+
+/// 重複したタスクを残すタスクに統合し、取り除いたタスクの数を返す
+///
+/// 取り除くタスクのサブタスクとメモは、残すタスクのサブタスクの後ろに移す。
+pub fn merge_duplicates(document: &mut TaskDocument, groups: &[DuplicateGroup]) -> usize {
+    // 後ろから取り除くと前の位置は変わらない。残すタスクの位置は取り除いた行数だけずらす
+    let mut removals: Vec<(usize, TaskLocation)> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(group, duplicates)| duplicates.merge.iter().map(move |&l| (group, l)))
+        .collect();
+    removals.sort_by_key(|(_, location)| std::cmp::Reverse(location.node));
+
+    let mut keeps: Vec<TaskLocation> = groups.iter().map(|group| group.keep).collect();
+    let mut moved: Vec<Vec<Node>> = vec![Vec::new(); groups.len()];
+    for (group, location) in &removals {
+        let block = document.remove_block(location);
+        for keep in keeps.iter_mut() {
+            if keep.section == location.section && keep.node > location.node {
+                keep.node -= block.len();
+            }
+        }
+        // ファイル内の順序を保つため、前のタスクのサブタスクを先頭に入れる
+        moved[*group].splice(0..0, block.into_iter().skip(1));
+    }
+
+    // 後ろの残すタスクから挿入すると前の位置は変わらない
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by_key(|&group| std::cmp::Reverse(keeps[group].node));
+    for group in order {
+        let keep = keeps[group];
+        let section = &mut document.sections[keep.section];
+        let end = section.block_end(keep.node);
+        section
+            .nodes
+            .splice(end..end, std::mem::take(&mut moved[group]));
+    }
+    removals.len()
+}
@@ -0,0 +1,127 @@
+/// 変更箇所の前後に表示する行数
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+// 編集操作1つと、その時点での変更前・変更後の行の位置
+#[derive(Debug, Clone, Copy)]
+struct Op {
+    kind: Kind,
+    old: usize,
+    new: usize,
+}
+
+// 最長共通部分列から行単位の編集操作を求める
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        let kind = if i < n && j < m && old[i] == new[j] {
+            Kind::Equal
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            Kind::Delete
+        } else {
+            Kind::Insert
+        };
+        ops.push(Op {
+            kind,
+            old: i,
+            new: j,
+        });
+        match kind {
+            Kind::Equal => {
+                i += 1;
+                j += 1;
+            }
+            Kind::Delete => i += 1,
+            Kind::Insert => j += 1,
+        }
+    }
+    ops
+}
+
+// 行を出力する。末尾に改行がない行には `\ No newline at end of file` を付ける
+fn push_line(output: &mut String, prefix: char, line: &str) {
+    output.push(prefix);
+    match line.strip_suffix('\n') {
+        Some(line) => {
+            output.push_str(line);
+            output.push('\n');
+        }
+        None => {
+            output.push_str(line);
+            output.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+// hunk の範囲（`start`、`count`）。空の範囲は直前の行番号で表す
+fn range(start: usize, count: usize) -> String {
+    if count == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, count)
+    }
+}
+
+/// 2つのテキストの unified diff。差分がなければ空文字列
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = (0..ops.len())
+        .filter(|&i| ops[i].kind != Kind::Equal)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let mut i = 0;
+    while i < changes.len() {
+        // 間の変更されていない行が少ない変更は1つの hunk にまとめる
+        let start = changes[i].saturating_sub(CONTEXT);
+        let mut last = changes[i];
+        while i + 1 < changes.len() && changes[i + 1] - last <= 2 * CONTEXT + 1 {
+            i += 1;
+            last = changes[i];
+        }
+        let end = (last + CONTEXT).min(ops.len() - 1);
+        i += 1;
+
+        let hunk = &ops[start..=end];
+        let old_count = hunk.iter().filter(|op| op.kind != Kind::Insert).count();
+        let new_count = hunk.iter().filter(|op| op.kind != Kind::Delete).count();
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(hunk[0].old, old_count),
+            range(hunk[0].new, new_count)
+        ));
+        for op in hunk {
+            match op.kind {
+                Kind::Equal => push_line(&mut output, ' ', old_lines[op.old]),
+                Kind::Delete => push_line(&mut output, '-', old_lines[op.old]),
+                Kind::Insert => push_line(&mut output, '+', new_lines[op.new]),
+            }
+        }
+    }
+    output
+}
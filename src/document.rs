@@ -0,0 +1,989 @@
+use crate::priority;
+use crate::storage::write_atomic;
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use std::collections::HashSet;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+
+/// タスク一覧セクションの見出し（デフォルト）
+pub const TASK_HEADING: &str = "## タスク一覧";
+/// アーカイブセクションの見出し（デフォルト）
+pub const ARCHIVE_HEADING: &str = "## アーカイブ";
+/// ゴミ箱セクションの見出し（デフォルト）
+pub const TRASH_HEADING: &str = "## ゴミ箱";
+/// 期日の記号（`📅 2024-12-31`）
+pub const DUE_SYMBOL: &str = "📅";
+/// 作成日の記号（`➕ 2024-12-31`）
+pub const CREATED_SYMBOL: &str = "➕";
+/// 完了日の記号（`✅ 2024-12-31`）
+pub const DONE_SYMBOL: &str = "✅";
+
+const CHECKBOX_START: &str = "- [";
+const CHECKBOX_END: &str = "] ";
+const META_START: &str = "<!--";
+const META_END: &str = "-->";
+
+/// 行頭の空白の幅（タブは4桁として数える）
+fn indent_width(indent: &str) -> usize {
+    indent.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// タスクの状態。チェックボックスの中の文字で表す
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TaskState {
+    /// `- [ ]` 未着手
+    Open,
+    /// `- [/]` 進行中
+    InProgress,
+    /// `- [?]` 保留（他の作業や返事を待っている）
+    Waiting,
+    /// `- [x]` 完了
+    Done,
+    /// `- [-]` 中止
+    Cancelled,
+}
+
+impl TaskState {
+    const ALL: [TaskState; 5] = [
+        TaskState::Open,
+        TaskState::InProgress,
+        TaskState::Waiting,
+        TaskState::Done,
+        TaskState::Cancelled,
+    ];
+
+    /// チェックボックスの中の文字
+    pub fn marker(self) -> char {
+        match self {
+            TaskState::Open => ' ',
+            TaskState::InProgress => '/',
+            TaskState::Waiting => '?',
+            TaskState::Done => 'x',
+            TaskState::Cancelled => '-',
+        }
+    }
+
+    pub fn from_marker(marker: char) -> Option<Self> {
+        TaskState::ALL
+            .into_iter()
+            .find(|state| state.marker() == marker)
+    }
+
+    /// `list --state` や `--json` で使う名前
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskState::Open => "open",
+            TaskState::InProgress => "in-progress",
+            TaskState::Waiting => "waiting",
+            TaskState::Done => "done",
+            TaskState::Cancelled => "cancelled",
+        }
+    }
+
+    /// 完了または中止。アーカイブの対象になり、未完了の一覧には表示しない
+    pub fn is_closed(self) -> bool {
+        matches!(self, TaskState::Done | TaskState::Cancelled)
+    }
+}
+
+/// チェックボックス付きの1行のタスク
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskItem {
+    /// 行頭のインデント。より深くインデントされた直後のタスクはサブタスクになる
+    pub indent: String,
+    pub state: TaskState,
+    /// チェックボックスより後ろの本文（優先度記号を含む）
+    pub content: String,
+}
+
+impl TaskItem {
+    pub fn new(state: TaskState, content: &str) -> Self {
+        TaskItem {
+            indent: String::new(),
+            state,
+            content: content.to_string(),
+        }
+    }
+
+    /// `- [ ] ...` / `- [x] ...` などの形式の行（インデント可）をタスクとして解釈する。
+    /// 状態を表さない文字のチェックボックスはタスクとして扱わない
+    pub fn parse(line: &str) -> Option<Self> {
+        let indent = leading_whitespace(line);
+        let rest = line[indent.len()..].strip_prefix(CHECKBOX_START)?;
+        let mut chars = rest.chars();
+        let state = TaskState::from_marker(chars.next()?)?;
+        let content = chars.as_str().strip_prefix(CHECKBOX_END)?;
+        let mut task = TaskItem::new(state, content);
+        task.indent = indent.to_string();
+        Some(task)
+    }
+
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}{}{}{}{}",
+            self.indent,
+            CHECKBOX_START,
+            self.state.marker(),
+            CHECKBOX_END,
+            self.content
+        )
+    }
+
+    /// 完了または中止
+    pub fn is_closed(&self) -> bool {
+        self.state.is_closed()
+    }
+
+    /// 優先度の段階（0が最も高い）。段階は `priority::scale()` に従う
+    pub fn priority_level(&self) -> Option<usize> {
+        priority::scale().find(&self.content)
+    }
+
+    /// 本文の先頭にある優先度記号
+    pub fn priority_symbol(&self) -> Option<&'static str> {
+        let level = self.priority_level()?;
+        Some(&priority::scale().levels[level].symbol)
+    }
+
+    /// 優先度名（`high` / `medium` / `low` など）
+    pub fn priority(&self) -> Option<&'static str> {
+        let level = self.priority_level()?;
+        Some(&priority::scale().levels[level].name)
+    }
+
+    /// 優先度記号とメタデータコメントを除いた本文
+    pub fn text(&self) -> &str {
+        let body = self.body();
+        match self.priority_symbol() {
+            Some(symbol) => body[symbol.len()..].trim_start(),
+            None => body,
+        }
+    }
+
+    /// 優先度記号とメタデータコメントを残したまま本文を置き換える
+    pub fn set_text(&mut self, text: &str) {
+        let body = match self.priority_symbol() {
+            Some(symbol) => format!("{} {}", symbol, text),
+            None => text.to_string(),
+        };
+        self.set_body(&body);
+    }
+
+    /// 優先度記号を置き換える（なければ先頭に追加する）
+    pub fn set_priority_symbol(&mut self, symbol: &str) {
+        let body = format!("{} {}", symbol, self.text());
+        self.set_body(&body);
+    }
+
+    /// 行末のメタデータコメント（`<!-- id:7f3a -->`）を分離する
+    fn split_meta(&self) -> (&str, Option<&str>) {
+        let content = self.content.trim_end();
+        if let Some(inner) = content.strip_suffix(META_END)
+            && let Some(start) = inner.rfind(META_START)
+        {
+            let body = content[..start].trim_end();
+            return (body, Some(inner[start + META_START.len()..].trim()));
+        }
+        (&self.content, None)
+    }
+
+    /// メタデータコメントを除いた本文（表示用）
+    pub fn body(&self) -> &str {
+        self.split_meta().0
+    }
+
+    /// メタデータコメントを残したまま本文を置き換える
+    fn set_body(&mut self, body: &str) {
+        self.content = match self.split_meta().1 {
+            Some(meta) => format!("{} {} {} {}", body, META_START, meta, META_END),
+            None => body.to_string(),
+        };
+    }
+
+    /// 本文中の `記号 YYYY-MM-DD` の範囲と日付
+    fn find_date_field(&self, symbol: &str) -> Option<(usize, usize, NaiveDate)> {
+        let body = self.body();
+        let mut offset = 0;
+        while let Some(pos) = body[offset..].find(symbol) {
+            let start = offset + pos;
+            let after = &body[start + symbol.len()..];
+            let value = after.trim_start();
+            let value_start = body.len() - value.len();
+            if let Some(date) = value
+                .get(..10)
+                .and_then(|v| NaiveDate::parse_from_str(v, crate::date::DATE_FORMAT).ok())
+            {
+                return Some((start, value_start + 10, date));
+            }
+            offset = start + symbol.len();
+        }
+        None
+    }
+
+    pub fn date_field(&self, symbol: &str) -> Option<NaiveDate> {
+        self.find_date_field(symbol).map(|(_, _, date)| date)
+    }
+
+    /// `記号 YYYY-MM-DD` を本文の末尾に設定する（`None` なら取り除く）
+    pub fn set_date_field(&mut self, symbol: &str, date: Option<NaiveDate>) {
+        let mut body = self.body().to_string();
+        if let Some((start, end, _)) = self.find_date_field(symbol) {
+            let start = if body[..start].ends_with(' ') {
+                start - 1
+            } else {
+                start
+            };
+            body.replace_range(start..end, "");
+        }
+        if let Some(date) = date {
+            body = format!(
+                "{} {} {}",
+                body,
+                symbol,
+                date.format(crate::date::DATE_FORMAT)
+            );
+        }
+        self.set_body(&body);
+    }
+
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.date_field(DUE_SYMBOL)
+    }
+
+    pub fn created(&self) -> Option<NaiveDate> {
+        self.date_field(CREATED_SYMBOL)
+    }
+
+    pub fn completed(&self) -> Option<NaiveDate> {
+        self.date_field(DONE_SYMBOL)
+    }
+
+    /// 本文中の `#tag` 形式のタグ（`#` を除いた名前、出現順）
+    pub fn tags(&self) -> Vec<&str> {
+        self.body()
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .filter(|tag| is_tag_name(tag))
+            .collect()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        self.tags().iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    pub fn meta(&self, key: &str) -> Option<&str> {
+        self.split_meta().1?.split_whitespace().find_map(|pair| {
+            let (k, v) = pair.split_once(':')?;
+            (k == key).then_some(v)
+        })
+    }
+
+    /// メタデータを設定する。既存のキーは値を置き換え、なければ末尾に追加する
+    pub fn set_meta(&mut self, key: &str, value: &str) {
+        let (body, meta) = self.split_meta();
+        let mut pairs: Vec<String> = meta
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter(|pair| pair.split_once(':').map(|(k, _)| k) != Some(key))
+            .map(str::to_string)
+            .collect();
+        pairs.push(format!("{}:{}", key, value));
+        self.content = format!("{} {} {} {}", body, META_START, pairs.join(" "), META_END);
+    }
+
+    /// メタデータを取り除く。残りがなければコメントごと取り除く
+    pub fn remove_meta(&mut self, key: &str) {
+        let (body, meta) = self.split_meta();
+        let Some(meta) = meta else {
+            return;
+        };
+        let pairs: Vec<&str> = meta
+            .split_whitespace()
+            .filter(|pair| pair.split_once(':').map(|(k, _)| k) != Some(key))
+            .collect();
+        self.content = if pairs.is_empty() {
+            body.to_string()
+        } else {
+            format!("{} {} {} {}", body, META_START, pairs.join(" "), META_END)
+        };
+    }
+
+    /// 並べ替えやアーカイブをしても変わらないタスクID
+    pub fn id(&self) -> Option<&str> {
+        self.meta("id")
+    }
+}
+
+/// コマンドでのタスクの指定方法。数字のみならタスク番号、それ以外はタスクID
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskRef {
+    Number(usize),
+    Id(String),
+}
+
+impl FromStr for TaskRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(number) = s.parse::<usize>() {
+            return Ok(TaskRef::Number(number));
+        }
+
+        // `id:7f3a` や `^7f3a` の形式も受け付ける
+        let id = s
+            .strip_prefix("id:")
+            .or_else(|| s.strip_prefix('^'))
+            .unwrap_or(s);
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("'{}' is not a task number or task ID", s));
+        }
+        Ok(TaskRef::Id(id.to_lowercase()))
+    }
+}
+
+impl fmt::Display for TaskRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskRef::Number(number) => write!(f, "{}", number),
+            TaskRef::Id(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+/// `done` / `remove` / `archive` でのタスクの指定。タスク番号・タスクID、または番号の範囲（`5-7`）
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskSelector {
+    Task(TaskRef),
+    /// 両端を含む番号の範囲
+    Range(usize, usize),
+}
+
+impl FromStr for TaskSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((start, end)) = s.split_once('-')
+            && let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>())
+        {
+            return Ok(TaskSelector::Range(start.min(end), start.max(end)));
+        }
+        s.parse().map(TaskSelector::Task)
+    }
+}
+
+impl fmt::Display for TaskSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskSelector::Task(task_ref) => write!(f, "{}", task_ref),
+            TaskSelector::Range(start, end) => write!(f, "{}-{}", start, end),
+        }
+    }
+}
+
+/// チェックボックスのように見えるがタスクとして解釈できない行（`- [>] ...`、`-[ ] ...` など）の行番号（1始まり）
+pub fn malformed_task_lines(contents: &str) -> Vec<usize> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            (trimmed.starts_with("- [") || trimmed.starts_with("-[") || trimmed.starts_with("* ["))
+                && TaskItem::parse(line).is_none()
+        })
+        .map(|(index, _)| index + 1)
+        .collect()
+}
+
+/// セクション内の1行分の要素
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Task(TaskItem),
+    /// タスク以外の行（空行やメモなど）はそのまま保持する
+    Text(String),
+}
+
+impl Node {
+    pub fn is_blank(&self) -> bool {
+        matches!(self, Node::Text(text) if text.trim().is_empty())
+    }
+
+    fn indent_width(&self) -> usize {
+        match self {
+            Node::Task(task) => indent_width(&task.indent),
+            Node::Text(text) => indent_width(leading_whitespace(text)),
+        }
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            Node::Task(task) => task.to_line(),
+            Node::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// 見出しから次の見出しまでの範囲。最初の見出しより前の行は `heading: None` のセクションになる
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub heading: Option<String>,
+    pub nodes: Vec<Node>,
+}
+
+impl Section {
+    pub fn new(heading: &str) -> Self {
+        Section {
+            heading: Some(heading.to_string()),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// 見出しの直後に空行を1行置いたセクション
+    pub fn with_blank_line(heading: &str) -> Self {
+        let mut section = Section::new(heading);
+        section.nodes.push(Node::Text(String::new()));
+        section
+    }
+
+    /// 見出しの `#` を除いたタイトル
+    pub fn title(&self) -> Option<&str> {
+        self.heading
+            .as_deref()
+            .map(|heading| heading.trim_start_matches('#').trim())
+    }
+
+    pub fn is(&self, heading: &str) -> bool {
+        self.heading.as_deref() == Some(heading)
+    }
+
+    /// 最後の空行でない要素の直後にタスクを挿入する
+    pub fn push_task(&mut self, task: TaskItem) {
+        self.push_nodes(vec![Node::Task(task)]);
+    }
+
+    /// 最後の空行でない要素の直後に複数の行をまとめて挿入し、挿入した位置を返す
+    pub fn push_nodes(&mut self, nodes: Vec<Node>) -> usize {
+        let position = self
+            .nodes
+            .iter()
+            .rposition(|node| !node.is_blank())
+            .map(|i| i + 1)
+            .unwrap_or(self.nodes.len());
+        self.nodes.splice(position..position, nodes);
+        position
+    }
+
+    /// 指定したタスクと、その下により深くインデントされた行（サブタスクやメモ）の範囲の終端
+    ///
+    /// 空行は、その後により深くインデントされた行が続く場合に限り範囲に含める。
+    pub fn block_end(&self, node: usize) -> usize {
+        let width = self.nodes[node].indent_width();
+        let mut end = node + 1;
+        for (i, next) in self.nodes.iter().enumerate().skip(node + 1) {
+            if next.is_blank() {
+                continue;
+            }
+            if next.indent_width() <= width {
+                break;
+            }
+            end = i + 1;
+        }
+        end
+    }
+}
+
+/// ドキュメント内のタスクの位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaskLocation {
+    /// ファイル全体での通し番号（1始まり）。全コマンド共通のタスク番号
+    pub number: usize,
+    pub section: usize,
+    pub node: usize,
+    /// ファイル上の行番号（1始まり）
+    pub line: usize,
+    /// サブタスクの深さ（トップレベルは0）
+    pub depth: usize,
+    /// 親タスクのタスク番号
+    pub parent: Option<usize>,
+}
+
+/// タスク一覧・アーカイブ・ゴミ箱として扱うセクションの見出し行
+#[derive(Debug, Clone, PartialEq)]
+pub struct Headings {
+    pub task: String,
+    pub archive: String,
+    pub trash: String,
+}
+
+impl Headings {
+    /// 見出しレベル（`#` の数）とタイトルから見出し行を組み立てる
+    pub fn new(level: usize, task_title: &str, archive_title: &str, trash_title: &str) -> Self {
+        let hashes = "#".repeat(level.clamp(1, 6));
+        Headings {
+            task: format!("{} {}", hashes, task_title),
+            archive: format!("{} {}", hashes, archive_title),
+            trash: format!("{} {}", hashes, trash_title),
+        }
+    }
+}
+
+impl Default for Headings {
+    fn default() -> Self {
+        Headings {
+            task: TASK_HEADING.to_string(),
+            archive: ARCHIVE_HEADING.to_string(),
+            trash: TRASH_HEADING.to_string(),
+        }
+    }
+}
+
+/// 行のまとまりのインデントを `indent` の分だけ浅くする（サブタスクを最上位のタスクとして移すため）
+pub fn dedent_block(nodes: &mut [Node], indent: &str) {
+    for node in nodes {
+        match node {
+            Node::Task(task) => {
+                if let Some(rest) = task.indent.strip_prefix(indent) {
+                    task.indent = rest.to_string();
+                }
+            }
+            Node::Text(line) => {
+                if let Some(rest) = line.strip_prefix(indent) {
+                    *line = rest.to_string();
+                }
+            }
+        }
+    }
+}
+
+/// タスクファイル全体の構造。`parse` と `Display` で内容を変えずに往復できる
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TaskDocument {
+    pub sections: Vec<Section>,
+    pub trailing_newline: bool,
+    /// 改行が `\r\n` か（最初の改行で判定し、書き出すときも同じ改行を使う）
+    pub crlf: bool,
+    pub headings: Headings,
+    /// 他のファイル（アーカイブファイルなど）のタスクのID。新しいIDはこれらも避ける
+    pub reserved_ids: HashSet<String>,
+}
+
+/// `seed` から決まるIDを返す。使用済みのIDに当たった場合は順に別の値を試す
+fn generate_unique_id(seed: &str, existing: &HashSet<&str>) -> String {
+    for salt in 0u64.. {
+        // DefaultHasher::new() は固定のキーを使うため、同じ入力からは同じIDになる
+        let mut hasher = DefaultHasher::new();
+        hasher.write(seed.as_bytes());
+        hasher.write_u64(salt);
+        let id = format!("{:04x}", hasher.finish() & 0xffff);
+
+        // 数字のみのIDはタスク番号と区別できないため使わない
+        if id.chars().any(|c| c.is_ascii_alphabetic()) && !existing.contains(id.as_str()) {
+            return id;
+        }
+    }
+    unreachable!()
+}
+
+/// タグ名として使える文字列か（英数字・`-`・`_`・`/` と非ASCII文字）
+pub fn is_tag_name(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+}
+
+fn is_heading(line: &str) -> bool {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    (1..=6).contains(&hashes) && (line.len() == hashes || line[hashes..].starts_with(' '))
+}
+
+impl TaskDocument {
+    pub fn parse(contents: &str) -> Self {
+        let mut document = TaskDocument::default();
+        if contents.is_empty() {
+            return document;
+        }
+
+        document.crlf = contents
+            .find('\n')
+            .is_some_and(|end| contents[..end].ends_with('\r'));
+        let body = match contents.strip_suffix('\n') {
+            Some(body) => {
+                document.trailing_newline = true;
+                body
+            }
+            None => contents,
+        };
+
+        for line in body.split('\n') {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if is_heading(line) {
+                document.sections.push(Section::new(line));
+                continue;
+            }
+
+            if document.sections.is_empty() {
+                document.sections.push(Section {
+                    heading: None,
+                    nodes: Vec::new(),
+                });
+            }
+            let node = match TaskItem::parse(line) {
+                Some(task) => Node::Task(task),
+                None => Node::Text(line.to_string()),
+            };
+            document.sections.last_mut().unwrap().nodes.push(node);
+        }
+
+        document
+    }
+
+    pub fn load(file_path: &str) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(file_path)?;
+        Ok(TaskDocument::parse(&contents))
+    }
+
+    /// タスク一覧・アーカイブとして扱う見出しを指定する
+    pub fn with_headings(mut self, headings: Headings) -> Self {
+        self.headings = headings;
+        self
+    }
+
+    pub fn with_reserved_ids(mut self, reserved_ids: HashSet<String>) -> Self {
+        self.reserved_ids = reserved_ids;
+        self
+    }
+
+    pub fn save(&self, file_path: &str) -> std::io::Result<()> {
+        write_atomic(file_path, &self.to_string())
+    }
+
+    pub fn section_index(&self, heading: &str) -> Option<usize> {
+        self.sections.iter().position(|section| section.is(heading))
+    }
+
+    pub fn is_archive(&self, section: usize) -> bool {
+        self.sections[section].is(&self.headings.archive)
+    }
+
+    pub fn is_trash(&self, section: usize) -> bool {
+        self.sections[section].is(&self.headings.trash)
+    }
+
+    /// ファイル先頭から順に全タスクの位置を列挙する。ゴミ箱のタスクは含まない
+    pub fn tasks(&self) -> Vec<TaskLocation> {
+        self.locate_tasks(|section| !self.is_trash(section))
+    }
+
+    /// ゴミ箱のタスクの位置。番号はゴミ箱の中での通し番号
+    pub fn trashed_tasks(&self) -> Vec<TaskLocation> {
+        self.locate_tasks(|section| self.is_trash(section))
+    }
+
+    // `include` が `true` のセクションのタスクに通し番号を付けて列挙する
+    fn locate_tasks(&self, include: impl Fn(usize) -> bool) -> Vec<TaskLocation> {
+        let mut locations = Vec::new();
+        let mut line = 0;
+
+        for (section_index, section) in self.sections.iter().enumerate() {
+            if section.heading.is_some() {
+                line += 1;
+            }
+            if !include(section_index) {
+                line += section.nodes.len();
+                continue;
+            }
+
+            // 祖先タスクの（インデント幅, タスク番号）
+            let mut ancestors: Vec<(usize, usize)> = Vec::new();
+            for (node_index, node) in section.nodes.iter().enumerate() {
+                line += 1;
+                if node.is_blank() {
+                    continue;
+                }
+
+                let width = node.indent_width();
+                while ancestors.last().is_some_and(|&(w, _)| w >= width) {
+                    ancestors.pop();
+                }
+
+                if let Node::Task(_) = node {
+                    let number = locations.len() + 1;
+                    locations.push(TaskLocation {
+                        number,
+                        section: section_index,
+                        node: node_index,
+                        line,
+                        depth: ancestors.len(),
+                        parent: ancestors.last().map(|&(_, number)| number),
+                    });
+                    ancestors.push((width, number));
+                }
+            }
+        }
+
+        locations
+    }
+
+    pub fn find_task(&self, task_ref: &TaskRef) -> Option<TaskLocation> {
+        self.find_in(self.tasks(), task_ref)
+    }
+
+    /// ゴミ箱の最上位のタスクを、ゴミ箱での番号またはIDで探す
+    pub fn find_trashed(&self, task_ref: &TaskRef) -> Option<TaskLocation> {
+        let trashed = self.trashed_tasks();
+        self.find_in(trashed, task_ref)
+            .filter(|location| location.depth == 0)
+    }
+
+    fn find_in(&self, locations: Vec<TaskLocation>, task_ref: &TaskRef) -> Option<TaskLocation> {
+        locations.into_iter().find(|location| match task_ref {
+            TaskRef::Number(number) => location.number == *number,
+            TaskRef::Id(id) => self.task(location).id() == Some(id.as_str()),
+        })
+    }
+
+    /// 他のタスクと重複しない新しいタスクID（英字を1文字以上含む4桁の16進数）
+    pub fn generate_id(&self) -> String {
+        // ゴミ箱から戻したタスクと重ならないよう、ゴミ箱のIDも避ける
+        let mut existing: HashSet<&str> = self
+            .tasks()
+            .iter()
+            .chain(&self.trashed_tasks())
+            .filter_map(|location| self.task(location).id())
+            .collect();
+        existing.extend(self.reserved_ids.iter().map(String::as_str));
+        // RandomStateは生成ごとに異なるキーを持つため、乱数源として使える
+        let seed = RandomState::new().build_hasher().finish().to_string();
+        generate_unique_id(&seed, &existing)
+    }
+
+    /// IDのないタスクとIDが重複しているタスクに新しいIDを割り当てる。割り当てた数を返す
+    ///
+    /// IDは本文から決まるため、同じ内容に対しては `fmt --diff` の表示と `fmt` の結果が一致する
+    pub fn assign_missing_ids(&mut self) -> usize {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut assigned = 0;
+        // 新しいIDはゴミ箱や他のファイルのIDも避ける
+        let mut avoid: HashSet<String> = self
+            .trashed_tasks()
+            .iter()
+            .filter_map(|location| self.task(location).id())
+            .map(str::to_string)
+            .collect();
+        avoid.extend(self.reserved_ids.iter().cloned());
+
+        for location in self.tasks() {
+            let task = self.task_mut(&location);
+            match task.id() {
+                Some(id) if seen.insert(id.to_string()) => {}
+                _ => {
+                    let id = generate_unique_id(
+                        task.body(),
+                        &seen.iter().chain(&avoid).map(String::as_str).collect(),
+                    );
+                    task.set_meta("id", &id);
+                    seen.insert(id);
+                    assigned += 1;
+                }
+            }
+        }
+
+        assigned
+    }
+
+    pub fn task(&self, location: &TaskLocation) -> &TaskItem {
+        match &self.sections[location.section].nodes[location.node] {
+            Node::Task(task) => task,
+            Node::Text(_) => panic!("no task at line {}", location.line),
+        }
+    }
+
+    pub fn task_mut(&mut self, location: &TaskLocation) -> &mut TaskItem {
+        match &mut self.sections[location.section].nodes[location.node] {
+            Node::Task(task) => task,
+            Node::Text(_) => panic!("no task at line {}", location.line),
+        }
+    }
+
+    /// 指定したタスクのサブタスク（孫以下を含む）の位置
+    pub fn descendants(&self, location: &TaskLocation) -> Vec<TaskLocation> {
+        let end = self.sections[location.section].block_end(location.node);
+        self.tasks()
+            .into_iter()
+            .filter(|other| {
+                other.section == location.section && other.node > location.node && other.node < end
+            })
+            .collect()
+    }
+
+    /// サブタスクの（完了数, 総数）。中止したサブタスクも完了として数える
+    pub fn progress(&self, location: &TaskLocation) -> (usize, usize) {
+        let descendants = self.descendants(location);
+        let done = descendants
+            .iter()
+            .filter(|descendant| self.task(descendant).is_closed())
+            .count();
+        (done, descendants.len())
+    }
+
+    /// 指定位置のタスクをサブタスクやメモと一緒に取り除いて返す。
+    /// 同じドキュメントの他の `TaskLocation` は無効になる
+    pub fn remove_block(&mut self, location: &TaskLocation) -> Vec<Node> {
+        let section = &mut self.sections[location.section];
+        let end = section.block_end(location.node);
+        section.nodes.drain(location.node..end).collect()
+    }
+
+    /// 重なり合わない複数のタスクをサブタスクごと取り除き、元の順序で返す
+    pub fn remove_blocks(&mut self, locations: &[TaskLocation]) -> Vec<Node> {
+        let mut blocks: Vec<Vec<Node>> = locations
+            .iter()
+            .rev()
+            .map(|location| self.remove_block(location))
+            .collect();
+        blocks.reverse();
+        blocks.into_iter().flatten().collect()
+    }
+
+    /// 親タスクの末尾にサブタスクを追加する。インデントは既存のサブタスクに合わせる
+    pub fn push_subtask(&mut self, parent: &TaskLocation, mut task: TaskItem) {
+        let indent = match self
+            .descendants(parent)
+            .iter()
+            .find(|d| d.parent == Some(parent.number))
+        {
+            Some(child) => self.task(child).indent.clone(),
+            None => format!("{}  ", self.task(parent).indent),
+        };
+        task.indent = indent;
+
+        let section = &mut self.sections[parent.section];
+        let end = section.block_end(parent.node);
+        section.nodes.insert(end, Node::Task(task));
+    }
+
+    /// 新しいタスクを追加するセクション。タスク一覧がなければアーカイブ（またはゴミ箱）の直前、それもなければ末尾
+    pub fn task_section_index(&mut self) -> usize {
+        if let Some(index) = self.section_index(&self.headings.task) {
+            return index;
+        }
+        let end = [&self.headings.archive, &self.headings.trash]
+            .iter()
+            .filter_map(|heading| self.section_index(heading))
+            .min();
+        match end {
+            Some(index) if index > 0 => index - 1,
+            Some(_) => {
+                self.sections
+                    .insert(0, Section::with_blank_line(&self.headings.task));
+                0
+            }
+            None if !self.sections.is_empty() => self.sections.len() - 1,
+            None => {
+                self.sections
+                    .push(Section::with_blank_line(&self.headings.task));
+                self.trailing_newline = true;
+                0
+            }
+        }
+    }
+
+    /// 見出し `heading` のセクションの位置。なければ末尾に作成する
+    pub fn section_index_or_create(&mut self, heading: &str) -> usize {
+        if let Some(index) = self.section_index(heading) {
+            return index;
+        }
+        if let Some(last) = self.sections.last_mut()
+            && !last.nodes.last().is_some_and(Node::is_blank)
+        {
+            last.nodes.push(Node::Text(String::new()));
+        }
+        self.sections.push(Section::new(heading));
+        self.sections.len() - 1
+    }
+
+    /// タスク（サブタスクを含む行のまとまり）をセクションの先頭（見出し直後の空行の後ろ）に追加する
+    pub fn push_blocks_front(&mut self, index: usize, blocks: Vec<Node>) {
+        let nodes = &mut self.sections[index].nodes;
+        if !nodes.first().is_some_and(Node::is_blank) {
+            nodes.insert(0, Node::Text(String::new()));
+        }
+        nodes.splice(1..1, blocks);
+    }
+
+    /// タスク（サブタスクを含む行のまとまり）をタスク一覧の優先度の位置に挿入する
+    ///
+    /// 優先度が同じか高い最上位のタスクの後ろ、低いタスクの前に入る。
+    /// 優先度のないタスクは末尾に追加する。挿入した位置（セクション, 行）を返す
+    pub fn insert_by_priority(&mut self, block: Vec<Node>) -> (usize, usize) {
+        let level = match block.first() {
+            Some(Node::Task(task)) => task.priority_level(),
+            _ => None,
+        };
+        let index = self.task_section_index();
+        let position = level.and_then(|level| {
+            self.tasks()
+                .into_iter()
+                .filter(|location| location.section == index && location.depth == 0)
+                .find(|location| {
+                    self.task(location)
+                        .priority_level()
+                        .is_none_or(|other| other > level)
+                })
+                .map(|location| location.node)
+        });
+        let position = match position {
+            Some(position) => {
+                self.sections[index].nodes.splice(position..position, block);
+                position
+            }
+            None => self.sections[index].push_nodes(block),
+        };
+        (index, position)
+    }
+
+    /// タスク（サブタスクを含む行のまとまり）をアーカイブセクションの先頭に追加する
+    pub fn push_archived(&mut self, blocks: Vec<Node>) {
+        if blocks.is_empty() {
+            return;
+        }
+        let index = self.section_index_or_create(&self.headings.archive.clone());
+        self.push_blocks_front(index, blocks);
+    }
+
+    /// タスク（サブタスクを含む行のまとまり）をゴミ箱セクションの先頭に追加する
+    pub fn push_trashed(&mut self, blocks: Vec<Node>) {
+        if blocks.is_empty() {
+            return;
+        }
+        let index = self.section_index_or_create(&self.headings.trash.clone());
+        self.push_blocks_front(index, blocks);
+    }
+}
+
+impl fmt::Display for TaskDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        for section in &self.sections {
+            if let Some(heading) = &section.heading {
+                lines.push(heading.clone());
+            }
+            lines.extend(section.nodes.iter().map(Node::to_line));
+        }
+
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        write!(f, "{}", lines.join(newline))?;
+        if self.trailing_newline {
+            write!(f, "{}", newline)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::config::ConfigError;
+use crate::i18n::Message;
+use crate::priority;
+use std::fmt;
+
+/// コマンドの失敗。`main` で標準エラー出力に表示し、`exit_code` で終了する
+#[derive(Debug)]
+pub enum MdTaskError {
+    /// 指定した番号・IDのタスクがない（または操作の対象にならない）
+    TaskNotFound(String),
+    /// アーカイブできる完了済みのタスクがない
+    CompletedTaskNotFound(String),
+    /// `--parent` で指定したタスクがない
+    ParentNotFound(String),
+    InvalidPriority(String),
+    InvalidDate(String),
+    /// `trash empty --older-than` の期間が不正
+    InvalidPeriod(String),
+    InvalidTag(String),
+    /// `archive` でタスクも `--all` も指定されていない
+    ArchiveTargetMissing,
+    Io(std::io::Error),
+    Config(ConfigError),
+    /// `config validate` で見つかった問題の数
+    InvalidConfig(usize),
+    /// 取り消せる操作がない
+    NothingToUndo,
+    /// やり直せる操作がない
+    NothingToRedo,
+    /// 操作の後にタスクファイルが変更されていて、取り消し・やり直しができない
+    FileChanged(String),
+    /// 他のプロセスがタスクファイルをロックしている（`pid` はロックしているプロセス）
+    FileLocked {
+        file: String,
+        pid: Option<u32>,
+    },
+    /// `fmt --check` でタスクファイルが正規化されていない
+    NotFormatted(String),
+    /// `--filter` に一致するタスクがない
+    NoMatchingTasks(String),
+    /// 確認で操作を取りやめた
+    Aborted,
+    /// エディタが失敗した（エディタのコマンド）
+    EditorFailed(String),
+    /// `task_management.on_duplicate = "refuse"` で、同じ本文の未完了タスクがある（番号）
+    DuplicateTask(usize),
+    /// `config install` でグローバル設定ファイルを作成できなかった
+    InstallConfigFailed(String),
+    /// `config init` でローカル設定ファイルを作成できなかった
+    CreateConfigFailed(String),
+}
+
+impl MdTaskError {
+    /// 終了コード
+    ///
+    /// - 1: ファイルの読み書き（またはエディタ）に失敗した
+    /// - 2: 引数が不正（clap の引数エラーと同じ）
+    /// - 3: 指定したタスクが見つからない
+    /// - 4: 設定ファイルに問題がある
+    /// - 5: 取り消し・やり直しができない
+    /// - 6: 他のプロセスがタスクファイルをロックしている
+    /// - 7: タスクファイルが正規化されていない（`fmt --check`）
+    /// - 8: 確認で操作を取りやめた
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            MdTaskError::Io(_)
+            | MdTaskError::EditorFailed(_)
+            | MdTaskError::InstallConfigFailed(_)
+            | MdTaskError::CreateConfigFailed(_) => 1,
+            MdTaskError::InvalidPriority(_)
+            | MdTaskError::InvalidDate(_)
+            | MdTaskError::InvalidPeriod(_)
+            | MdTaskError::InvalidTag(_)
+            | MdTaskError::ArchiveTargetMissing
+            | MdTaskError::DuplicateTask(_) => 2,
+            MdTaskError::TaskNotFound(_)
+            | MdTaskError::CompletedTaskNotFound(_)
+            | MdTaskError::ParentNotFound(_)
+            | MdTaskError::NoMatchingTasks(_) => 3,
+            MdTaskError::Config(_) | MdTaskError::InvalidConfig(_) => 4,
+            MdTaskError::NothingToUndo
+            | MdTaskError::NothingToRedo
+            | MdTaskError::FileChanged(_) => 5,
+            MdTaskError::FileLocked { .. } => 6,
+            MdTaskError::NotFormatted(_) => 7,
+            MdTaskError::Aborted => 8,
+        }
+    }
+}
+
+impl fmt::Display for MdTaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MdTaskError::TaskNotFound(task) => write!(f, "{}", Message::TaskNotFound(task)),
+            MdTaskError::CompletedTaskNotFound(task) => {
+                write!(f, "{}", Message::CompletedTaskNotFound(task))
+            }
+            MdTaskError::ParentNotFound(task) => write!(f, "{}", Message::ParentNotFound(task)),
+            MdTaskError::InvalidPriority(priority) => write!(
+                f,
+                "{}",
+                Message::InvalidPriority {
+                    priority,
+                    names: &priority::scale().names(),
+                }
+            ),
+            MdTaskError::InvalidDate(date) => write!(f, "{}", Message::InvalidDate(date)),
+            MdTaskError::InvalidPeriod(period) => write!(f, "{}", Message::InvalidPeriod(period)),
+            MdTaskError::InvalidTag(tag) => write!(f, "{}", Message::InvalidTag(tag)),
+            MdTaskError::ArchiveTargetMissing => write!(f, "{}", Message::ArchiveTargetMissing),
+            MdTaskError::Io(e) => write!(f, "{}", Message::IoError(&e.to_string())),
+            MdTaskError::Config(e) => write!(f, "{}", e),
+            MdTaskError::InvalidConfig(problems) => {
+                write!(f, "{}", Message::ConfigProblems(*problems))
+            }
+            MdTaskError::NothingToUndo => write!(f, "{}", Message::NothingToUndo),
+            MdTaskError::NothingToRedo => write!(f, "{}", Message::NothingToRedo),
+            MdTaskError::FileChanged(file) => write!(f, "{}", Message::FileChanged(file)),
+            MdTaskError::FileLocked { file, pid } => {
+                write!(f, "{}", Message::FileLocked { file, pid: *pid })
+            }
+            MdTaskError::NotFormatted(file) => write!(f, "{}", Message::NotFormatted(file)),
+            MdTaskError::NoMatchingTasks(filter) => {
+                write!(f, "{}", Message::NoMatchingTasks(filter))
+            }
+            MdTaskError::Aborted => write!(f, "{}", Message::Aborted),
+            MdTaskError::EditorFailed(editor) => write!(f, "{}", Message::EditorFailed(editor)),
+            MdTaskError::DuplicateTask(number) => {
+                write!(f, "{}", Message::DuplicateTask(*number))
+            }
+            MdTaskError::InstallConfigFailed(error) => {
+                write!(f, "{}", Message::InstallConfigFailed(error))
+            }
+            MdTaskError::CreateConfigFailed(error) => {
+                write!(f, "{}", Message::CreateConfigFailed(error))
+            }
+        }
+    }
+}
+
+impl std::error::Error for MdTaskError {}
+
+impl From<std::io::Error> for MdTaskError {
+    fn from(e: std::io::Error) -> Self {
+        MdTaskError::Io(e)
+    }
+}
+
+impl From<ConfigError> for MdTaskError {
+    fn from(e: ConfigError) -> Self {
+        MdTaskError::Config(e)
+    }
+}
@@ -0,0 +1,633 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+/// CLIのメッセージの表示言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Ja,
+}
+
+impl Locale {
+    // `ja` / `ja_JP.UTF-8` / `en_US` のようなロケール名から判定する
+    fn parse(name: &str) -> Option<Locale> {
+        let name = name.trim().to_lowercase();
+        if name.starts_with("ja") {
+            Some(Locale::Ja)
+        } else if name.starts_with("en") || name == "c" || name == "posix" {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+
+    /// 環境変数 `LC_ALL` / `LC_MESSAGES` / `LANG` から表示言語を判定する（判定できなければ英語）
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::parse(&value))
+            .unwrap_or(Locale::En)
+    }
+
+    /// `display.locale` の設定値から表示言語を決める（`auto` の場合は環境変数から判定）
+    pub fn from_setting(setting: &str) -> Locale {
+        Locale::parse(setting).unwrap_or_else(Locale::from_env)
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// 表示言語を設定する（設定の読み込み後に一度だけ呼び出す）
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// 現在の表示言語。設定前は環境変数から判定する
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_else(Locale::from_env)
+}
+
+/// ユーザーに表示するメッセージ
+///
+/// `Display` で現在の表示言語の文字列になる。
+#[derive(Debug, Clone, Copy)]
+pub enum Message<'a> {
+    // 入力エラー
+    /// `names` は有効な優先度名の一覧
+    InvalidPriority {
+        priority: &'a str,
+        names: &'a str,
+    },
+    InvalidDate(&'a str),
+    InvalidPeriod(&'a str),
+    InvalidTag(&'a str),
+
+    // タスク操作
+    TaskAdded {
+        task: &'a str,
+        priority: &'a str,
+        id: &'a str,
+    },
+    ParentNotFound(&'a str),
+    TaskDone(&'a str),
+    TaskNotFound(&'a str),
+    TaskRemoved(&'a str),
+    AllArchived,
+    TaskArchived(&'a str),
+    CompletedTaskNotFound(&'a str),
+    ArchiveTargetMissing,
+    NoCompletedTasks,
+    ArchivedCount(usize),
+    HeadingsRenamed(usize),
+    TasksSorted(usize),
+    /// 内容を変更した正規化ルール（`, ` 区切り）
+    FileNormalized(&'a str),
+
+    // 一覧表示
+    NoTasks,
+    TasksHeader,
+    AllTasksHeader,
+    ArchivedHeader,
+    StateOpen,
+    StateInProgress,
+    StateWaiting,
+    StateDone,
+    StateCancelled,
+    StateArchived,
+    ListSummary {
+        open: usize,
+        done: usize,
+        archived: usize,
+    },
+    TagsHeader,
+    TagSummary {
+        tag: &'a str,
+        open: usize,
+        done: usize,
+    },
+    Overdue(i64),
+    DueToday,
+    DueIn(i64),
+
+    // 設定
+    LoadedConfig(&'a str),
+    CreatedLocalConfig(&'a str),
+    CreatedGlobalConfig(&'a str),
+    GlobalConfigExists(&'a str),
+    InstallConfigFailed(&'a str),
+    CreateConfigFailed(&'a str),
+    ConfigStatusTitle,
+    ConfigSearchOrder,
+    Exists,
+    NotFound,
+    EnvironmentVariables,
+    Environment,
+    NotSet,
+    ProjectRoot,
+    NotDetected,
+    ActiveConfig,
+    DefaultSource,
+    ConfigReadError {
+        path: &'a str,
+        error: &'a str,
+    },
+    ConfigParseError {
+        path: &'a str,
+        line: usize,
+        column: usize,
+        message: &'a str,
+    },
+    UnknownConfigKey {
+        path: &'a str,
+        key: &'a str,
+    },
+    InvalidConfigPriority {
+        path: &'a str,
+        value: &'a str,
+        names: &'a str,
+    },
+    EmptyPriorityScale(&'a str),
+    EmptyPriorityField(&'a str),
+    DuplicatePriority {
+        path: &'a str,
+        value: &'a str,
+    },
+    ConfigSkipped(&'a str),
+    ConfigValid(&'a str),
+    ConfigProblems(usize),
+    IoError(&'a str),
+
+    // 操作履歴
+    Undone(&'a str),
+    Redone(&'a str),
+    NothingToUndo,
+    NothingToRedo,
+    FileChanged(&'a str),
+    HistoryHeader,
+    NoHistory,
+    HistoryUndone,
+    FileLocked {
+        file: &'a str,
+        pid: Option<u32>,
+    },
+
+    // fmt
+    Formatted(&'a str),
+    AlreadyFormatted(&'a str),
+    NotFormatted(&'a str),
+
+    // 一括操作（引数はタスク番号の一覧）
+    TasksDone(&'a str),
+    TasksRemoved(&'a str),
+    TasksArchived(&'a str),
+    TaskReopened(&'a str),
+    TasksReopened(&'a str),
+    TasksStarted(&'a str),
+    TasksWaiting(&'a str),
+    TasksCancelled(&'a str),
+    NoMatchingTasks(&'a str),
+    /// 削除の確認（件数）
+    ConfirmRemove(usize),
+    Aborted,
+
+    // 重複したタスク（引数はタスク番号）
+    DuplicateTaskWarning(usize),
+    DuplicateTask(usize),
+    DuplicatesExact,
+    DuplicatesSimilar,
+    ConfirmMerge,
+    DuplicatesMerged(usize),
+    NoDuplicates,
+
+    // 編集（引数は指定されたタスク番号・ID）
+    TaskEdited(&'a str),
+    PriorityChanged {
+        task: &'a str,
+        priority: &'a str,
+    },
+    MalformedTaskLine(usize),
+    EditorFailed(&'a str),
+
+    // ゴミ箱
+    TrashHeader,
+    TrashEmpty,
+    /// 削除した日付
+    RemovedOn(&'a str),
+    TaskRestored(&'a str),
+    TrashEmptied(usize),
+}
+
+impl Message<'_> {
+    /// 指定した言語での文字列
+    pub fn text(&self, locale: Locale) -> String {
+        use Message::*;
+        match locale {
+            Locale::En => match *self {
+                InvalidPriority { priority, names } => format!(
+                    "ERROR: Invalid priority '{}'. Use one of: {}",
+                    priority, names
+                ),
+                InvalidDate(date) => format!(
+                    "ERROR: Invalid date '{}'. Use YYYY-MM-DD, today, tomorrow, +3d, +2w or next-mon",
+                    date
+                ),
+                InvalidPeriod(period) => {
+                    format!("ERROR: Invalid period '{}'. Use 30d, 2w or 30", period)
+                }
+                InvalidTag(tag) => format!(
+                    "ERROR: Invalid tag '{}'. Use letters, digits, '-', '_' or '/'",
+                    tag
+                ),
+                TaskAdded { task, priority, id } => {
+                    format!("Task added: {} ({} priority) [{}]", task, priority, id)
+                }
+                ParentNotFound(task) => format!("ERROR: Parent task {} not found.", task),
+                TaskDone(task) => format!("Task {} marked as done.", task),
+                TaskNotFound(task) => format!("ERROR: Task {} not found.", task),
+                TaskRemoved(task) => format!("Task {} removed.", task),
+                AllArchived => "All completed tasks have been archived.".to_string(),
+                TaskArchived(task) => format!("Task {} archived successfully.", task),
+                CompletedTaskNotFound(task) => {
+                    format!("ERROR: Completed task {} not found.", task)
+                }
+                ArchiveTargetMissing => {
+                    "ERROR: Please specify either --all or a task number.".to_string()
+                }
+                NoCompletedTasks => "No completed tasks to archive.".to_string(),
+                ArchivedCount(count) => format!("Archived {} completed tasks.", count),
+                HeadingsRenamed(count) => format!("{} heading(s) renamed.", count),
+                TasksSorted(count) => format!("Sorted {} task(s).", count),
+                FileNormalized(rules) => format!("File format normalized ({}).", rules),
+                NoTasks => "No tasks found. Please add a task first.".to_string(),
+                TasksHeader => "--- Tasks ---".to_string(),
+                AllTasksHeader => "--- All Tasks ---".to_string(),
+                ArchivedHeader => "--- Archived ---".to_string(),
+                StateOpen => "open".to_string(),
+                StateInProgress => "in progress".to_string(),
+                StateWaiting => "waiting".to_string(),
+                StateDone => "done".to_string(),
+                StateCancelled => "cancelled".to_string(),
+                StateArchived => "archived".to_string(),
+                ListSummary {
+                    open,
+                    done,
+                    archived,
+                } => format!("Total: {} open, {} done, {} archived", open, done, archived),
+                TagsHeader => "--- Tags ---".to_string(),
+                TagSummary { tag, open, done } => {
+                    format!("#{}: {} open, {} done", tag, open, done)
+                }
+                Overdue(days) => format!("⚠️ overdue ({} day(s) late)", days),
+                DueToday => "⏰ due today".to_string(),
+                DueIn(days) => format!("(due in {} day(s))", days),
+                LoadedConfig(path) => format!("Loaded config from: {}", path),
+                CreatedLocalConfig(path) => format!("Created local config file: {}", path),
+                CreatedGlobalConfig(path) => format!("Created global config file: {}", path),
+                GlobalConfigExists(path) => {
+                    format!("Global config file already exists: {}", path)
+                }
+                InstallConfigFailed(error) => {
+                    format!("ERROR: Failed to install the global config: {}", error)
+                }
+                CreateConfigFailed(error) => {
+                    format!("ERROR: Failed to create the config file: {}", error)
+                }
+                ConfigStatusTitle => "=== md-task Configuration Status ===".to_string(),
+                ConfigSearchOrder => "Configuration file search order:".to_string(),
+                Exists => "exists".to_string(),
+                NotFound => "not found".to_string(),
+                EnvironmentVariables => "Environment variables:".to_string(),
+                Environment => "Environment:".to_string(),
+                NotSet => "not set".to_string(),
+                ProjectRoot => "Project root".to_string(),
+                NotDetected => "not detected".to_string(),
+                ActiveConfig => "Current active configuration:".to_string(),
+                DefaultSource => "default".to_string(),
+                ConfigReadError { path, error } => {
+                    format!("ERROR: Cannot read config file {}: {}", path, error)
+                }
+                ConfigParseError {
+                    path,
+                    line,
+                    column,
+                    message,
+                } => format!(
+                    "ERROR: {}:{}:{}: invalid config: {}",
+                    path, line, column, message
+                ),
+                UnknownConfigKey { path, key } => {
+                    format!("WARNING: {}: unknown key '{}'", path, key)
+                }
+                InvalidConfigPriority { path, value, names } => format!(
+                    "ERROR: {}: invalid default_priority '{}'. Use one of: {}",
+                    path, value, names
+                ),
+                EmptyPriorityScale(path) => {
+                    format!("ERROR: {}: priority.levels must not be empty", path)
+                }
+                EmptyPriorityField(path) => format!(
+                    "ERROR: {}: every priority level needs a name and a symbol",
+                    path
+                ),
+                DuplicatePriority { path, value } => format!(
+                    "ERROR: {}: '{}' is used by more than one priority level",
+                    path, value
+                ),
+                ConfigSkipped(path) => format!(
+                    "WARNING: Ignoring {} and using the remaining settings (use --strict-config to abort instead)",
+                    path
+                ),
+                ConfigValid(path) => format!("{}: OK", path),
+                ConfigProblems(count) => format!("{} problem(s) found.", count),
+                IoError(error) => format!("ERROR: {}", error),
+                Undone(command) => format!("Undone: {}", command),
+                Redone(command) => format!("Redone: {}", command),
+                NothingToUndo => "ERROR: Nothing to undo.".to_string(),
+                NothingToRedo => "ERROR: Nothing to redo.".to_string(),
+                FileChanged(file) => format!(
+                    "ERROR: {} was modified after the recorded operation. Undo/redo would overwrite those changes.",
+                    file
+                ),
+                HistoryHeader => "--- History ---".to_string(),
+                NoHistory => "No operations recorded.".to_string(),
+                HistoryUndone => "(undone)".to_string(),
+                FileLocked {
+                    file,
+                    pid: Some(pid),
+                } => format!(
+                    "ERROR: {} is locked by pid {}. Try again after that command finishes.",
+                    file, pid
+                ),
+                FileLocked { file, pid: None } => format!(
+                    "ERROR: {} is locked by another process. Try again after that command finishes.",
+                    file
+                ),
+                Formatted(file) => format!("Formatted {}.", file),
+                AlreadyFormatted(file) => format!("{} is already formatted.", file),
+                NotFormatted(file) => format!(
+                    "ERROR: {} is not formatted. Run `md-task fmt` to fix it.",
+                    file
+                ),
+                TasksDone(tasks) => format!("Tasks {} marked as done.", tasks),
+                TasksRemoved(tasks) => format!("Tasks {} removed.", tasks),
+                TasksArchived(tasks) => format!("Tasks {} archived successfully.", tasks),
+                TaskReopened(task) => format!("Task {} reopened.", task),
+                TasksReopened(tasks) => format!("Tasks {} reopened.", tasks),
+                TasksStarted(tasks) => format!("Task(s) {} marked as in progress.", tasks),
+                TasksWaiting(tasks) => format!("Task(s) {} marked as waiting.", tasks),
+                TasksCancelled(tasks) => format!("Task(s) {} cancelled.", tasks),
+                NoMatchingTasks(filter) => format!("ERROR: No tasks match '{}'.", filter),
+                ConfirmRemove(count) => {
+                    format!("Remove these {} task(s) and their subtasks? [y/N] ", count)
+                }
+                Aborted => "Aborted.".to_string(),
+                DuplicateTaskWarning(number) => format!(
+                    "WARNING: Task {} has the same text. Run `md-task dedupe` to merge them.",
+                    number
+                ),
+                DuplicateTask(number) => format!(
+                    "ERROR: Task {} already has the same text (task_management.on_duplicate = \"refuse\").",
+                    number
+                ),
+                DuplicatesExact => "Duplicate tasks:".to_string(),
+                DuplicatesSimilar => {
+                    "Similar tasks (differ only in priority, spacing or width):".to_string()
+                }
+                ConfirmMerge => "Merge into the task marked with '*'? [y/N] ".to_string(),
+                DuplicatesMerged(count) => format!("Merged {} duplicate task(s).", count),
+                NoDuplicates => "No duplicate tasks found.".to_string(),
+                TaskEdited(task) => format!("Task {} updated.", task),
+                PriorityChanged { task, priority } => {
+                    format!("Task {} priority set to {}.", task, priority)
+                }
+                MalformedTaskLine(line) => format!(
+                    "WARNING: Line {} looks like a task but is not one (use - [ ], - [/], - [?], - [x] or - [-]).",
+                    line
+                ),
+                EditorFailed(editor) => format!("ERROR: Editor '{}' exited with an error.", editor),
+                TrashHeader => "--- Trash ---".to_string(),
+                TrashEmpty => "The trash is empty.".to_string(),
+                RemovedOn(date) => format!("removed {}", date),
+                TaskRestored(task) => format!("Task {} restored from the trash.", task),
+                TrashEmptied(count) => {
+                    format!("Permanently deleted {} task(s) from the trash.", count)
+                }
+            },
+            Locale::Ja => match *self {
+                InvalidPriority { priority, names } => format!(
+                    "エラー: 優先度 '{}' は無効です。次のいずれかを指定してください: {}",
+                    priority, names
+                ),
+                InvalidDate(date) => format!(
+                    "エラー: 日付 '{}' は無効です。YYYY-MM-DD、今日、明日、+3d、+2w、来週月曜 などを指定してください",
+                    date
+                ),
+                InvalidPeriod(period) => format!(
+                    "エラー: 期間 '{}' は無効です。30d、2w、30 などを指定してください",
+                    period
+                ),
+                InvalidTag(tag) => format!(
+                    "エラー: タグ '{}' は無効です。英数字、'-'、'_'、'/' が使えます",
+                    tag
+                ),
+                TaskAdded { task, priority, id } => {
+                    format!(
+                        "タスクを追加しました: {} (優先度 {}) [{}]",
+                        task, priority, id
+                    )
+                }
+                ParentNotFound(task) => format!("エラー: 親タスク {} が見つかりません。", task),
+                TaskDone(task) => format!("タスク {} を完了にしました。", task),
+                TaskNotFound(task) => format!("エラー: タスク {} が見つかりません。", task),
+                TaskRemoved(task) => format!("タスク {} を削除しました。", task),
+                AllArchived => "完了済みのタスクを全てアーカイブしました。".to_string(),
+                TaskArchived(task) => format!("タスク {} をアーカイブしました。", task),
+                CompletedTaskNotFound(task) => {
+                    format!("エラー: 完了済みのタスク {} が見つかりません。", task)
+                }
+                ArchiveTargetMissing => {
+                    "エラー: --all またはタスク番号を指定してください。".to_string()
+                }
+                NoCompletedTasks => "アーカイブする完了済みのタスクはありません。".to_string(),
+                ArchivedCount(count) => {
+                    format!("完了済みのタスクを{}件アーカイブしました。", count)
+                }
+                HeadingsRenamed(count) => format!("見出しを{}件置き換えました。", count),
+                TasksSorted(count) => format!("{}件のタスクを並べ替えました。", count),
+                FileNormalized(rules) => format!("ファイル形式を正規化しました（{}）。", rules),
+                NoTasks => "タスクがありません。まずタスクを追加してください。".to_string(),
+                TasksHeader => "--- タスク ---".to_string(),
+                AllTasksHeader => "--- 全てのタスク ---".to_string(),
+                ArchivedHeader => "--- アーカイブ済み ---".to_string(),
+                StateOpen => "未完了".to_string(),
+                StateInProgress => "進行中".to_string(),
+                StateWaiting => "保留".to_string(),
+                StateDone => "完了済み".to_string(),
+                StateCancelled => "中止".to_string(),
+                StateArchived => "アーカイブ済み".to_string(),
+                ListSummary {
+                    open,
+                    done,
+                    archived,
+                } => format!(
+                    "合計: 未完了 {}件, 完了済み {}件, アーカイブ済み {}件",
+                    open, done, archived
+                ),
+                TagsHeader => "--- タグ ---".to_string(),
+                TagSummary { tag, open, done } => {
+                    format!("#{}: 未完了 {}件, 完了済み {}件", tag, open, done)
+                }
+                Overdue(days) => format!("⚠️ 期限切れ ({}日超過)", days),
+                DueToday => "⏰ 今日まで".to_string(),
+                DueIn(days) => format!("(あと{}日)", days),
+                LoadedConfig(path) => format!("設定を読み込みました: {}", path),
+                CreatedLocalConfig(path) => format!("ローカル設定ファイルを作成しました: {}", path),
+                CreatedGlobalConfig(path) => {
+                    format!("グローバル設定ファイルを作成しました: {}", path)
+                }
+                GlobalConfigExists(path) => {
+                    format!("グローバル設定ファイルは既に存在します: {}", path)
+                }
+                InstallConfigFailed(error) => {
+                    format!(
+                        "エラー: グローバル設定のインストールに失敗しました: {}",
+                        error
+                    )
+                }
+                CreateConfigFailed(error) => {
+                    format!("エラー: 設定ファイルの作成に失敗しました: {}", error)
+                }
+                ConfigStatusTitle => "=== md-task 設定状況 ===".to_string(),
+                ConfigSearchOrder => "設定ファイルの検索順:".to_string(),
+                Exists => "あり".to_string(),
+                NotFound => "なし".to_string(),
+                EnvironmentVariables => "環境変数:".to_string(),
+                Environment => "環境:".to_string(),
+                NotSet => "未設定".to_string(),
+                ProjectRoot => "プロジェクトルート".to_string(),
+                NotDetected => "検出されません".to_string(),
+                ActiveConfig => "現在の有効な設定:".to_string(),
+                DefaultSource => "デフォルト".to_string(),
+                ConfigReadError { path, error } => {
+                    format!("エラー: 設定ファイル {} を読み込めません: {}", path, error)
+                }
+                ConfigParseError {
+                    path,
+                    line,
+                    column,
+                    message,
+                } => format!(
+                    "エラー: {}:{}:{}: 設定ファイルを解析できません: {}",
+                    path, line, column, message
+                ),
+                UnknownConfigKey { path, key } => {
+                    format!("警告: {}: 不明なキー '{}'", path, key)
+                }
+                InvalidConfigPriority { path, value, names } => format!(
+                    "エラー: {}: default_priority '{}' は無効です。次のいずれかを指定してください: {}",
+                    path, value, names
+                ),
+                EmptyPriorityScale(path) => {
+                    format!("エラー: {}: priority.levels が空です", path)
+                }
+                EmptyPriorityField(path) => {
+                    format!("エラー: {}: 優先度の段階には名前と記号が必要です", path)
+                }
+                DuplicatePriority { path, value } => format!(
+                    "エラー: {}: '{}' が複数の優先度の段階で使われています",
+                    path, value
+                ),
+                ConfigSkipped(path) => format!(
+                    "警告: {} を無視して残りの設定を使用します（中止するには --strict-config を指定してください）",
+                    path
+                ),
+                ConfigValid(path) => format!("{}: 問題ありません", path),
+                ConfigProblems(count) => format!("{}件の問題が見つかりました。", count),
+                IoError(error) => format!("エラー: {}", error),
+                Undone(command) => format!("取り消しました: {}", command),
+                Redone(command) => format!("やり直しました: {}", command),
+                NothingToUndo => "エラー: 取り消せる操作はありません。".to_string(),
+                NothingToRedo => "エラー: やり直せる操作はありません。".to_string(),
+                FileChanged(file) => format!(
+                    "エラー: {} は記録された操作の後に変更されています。取り消し・やり直しをするとその変更が失われます。",
+                    file
+                ),
+                HistoryHeader => "--- 操作履歴 ---".to_string(),
+                NoHistory => "操作の履歴はありません。".to_string(),
+                HistoryUndone => "(取り消し済み)".to_string(),
+                FileLocked {
+                    file,
+                    pid: Some(pid),
+                } => format!(
+                    "エラー: {} は pid {} によってロックされています。そのコマンドの終了後に再実行してください。",
+                    file, pid
+                ),
+                FileLocked { file, pid: None } => format!(
+                    "エラー: {} は他のプロセスによってロックされています。そのコマンドの終了後に再実行してください。",
+                    file
+                ),
+                Formatted(file) => format!("{} を正規化しました。", file),
+                AlreadyFormatted(file) => format!("{} は正規化済みです。", file),
+                NotFormatted(file) => format!(
+                    "エラー: {} は正規化されていません。`md-task fmt` で修正できます。",
+                    file
+                ),
+                TasksDone(tasks) => format!("タスク {} を完了にしました。", tasks),
+                TasksRemoved(tasks) => format!("タスク {} を削除しました。", tasks),
+                TasksArchived(tasks) => format!("タスク {} をアーカイブしました。", tasks),
+                TaskReopened(task) => format!("タスク {} を未完了に戻しました。", task),
+                TasksReopened(tasks) => format!("タスク {} を未完了に戻しました。", tasks),
+                TasksStarted(tasks) => format!("タスク {} を進行中にしました。", tasks),
+                TasksWaiting(tasks) => format!("タスク {} を保留にしました。", tasks),
+                TasksCancelled(tasks) => format!("タスク {} を中止しました。", tasks),
+                NoMatchingTasks(filter) => {
+                    format!("エラー: '{}' に一致するタスクはありません。", filter)
+                }
+                ConfirmRemove(count) => format!(
+                    "これらの{}件のタスクをサブタスクごと削除しますか？ [y/N] ",
+                    count
+                ),
+                Aborted => "中止しました。".to_string(),
+                DuplicateTaskWarning(number) => format!(
+                    "警告: タスク {} と同じ内容です。`md-task dedupe` で統合できます。",
+                    number
+                ),
+                DuplicateTask(number) => format!(
+                    "エラー: タスク {} と同じ内容のため追加しません（task_management.on_duplicate = \"refuse\"）。",
+                    number
+                ),
+                DuplicatesExact => "重複したタスク:".to_string(),
+                DuplicatesSimilar => "似たタスク（優先度・空白・全角半角のみの違い）:".to_string(),
+                ConfirmMerge => "'*' の付いたタスクに統合しますか？ [y/N] ".to_string(),
+                DuplicatesMerged(count) => format!("重複した{}件のタスクを統合しました。", count),
+                NoDuplicates => "重複したタスクはありません。".to_string(),
+                TaskEdited(task) => format!("タスク {} を更新しました。", task),
+                PriorityChanged { task, priority } => {
+                    format!("タスク {} の優先度を {} にしました。", task, priority)
+                }
+                MalformedTaskLine(line) => format!(
+                    "警告: {} 行目はタスクとして読み込めません（- [ ]、- [/]、- [?]、- [x]、- [-] のいずれかで始めてください）。",
+                    line
+                ),
+                EditorFailed(editor) => {
+                    format!("エラー: エディタ '{}' がエラーで終了しました。", editor)
+                }
+                TrashHeader => "--- ゴミ箱 ---".to_string(),
+                TrashEmpty => "ゴミ箱は空です。".to_string(),
+                RemovedOn(date) => format!("{} に削除", date),
+                TaskRestored(task) => format!("タスク {} をゴミ箱から戻しました。", task),
+                TrashEmptied(count) => {
+                    format!("ゴミ箱の{}件のタスクを完全に削除しました。", count)
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text(locale()))
+    }
+}
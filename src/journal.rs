@@ -0,0 +1,198 @@
+use crate::error::MdTaskError;
+use crate::storage::write_atomic;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 設定ディレクトリ内の操作履歴ファイル名
+pub const JOURNAL_FILE: &str = "journal.json";
+
+/// 履歴に残す操作の最大数（古いものから削除）
+pub const MAX_ENTRIES: usize = 100;
+
+/// 1回の変更操作。変更前後のファイル内容を持ち、取り消し・やり直しに使う
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    /// 実行日時（ローカルタイムゾーン）
+    pub timestamp: String,
+    /// 実行したコマンド（例: `rm 3`）
+    pub command: String,
+    /// 対象のタスクファイルの絶対パス
+    pub file: String,
+    /// 変更前の内容（ファイルがなかった場合は `None`）
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// 同じ操作で変更したタスクファイル以外のファイル（アーカイブファイルなど）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<FileChange>,
+    /// 取り消し済みかどうか
+    #[serde(default)]
+    pub undone: bool,
+}
+
+/// タスクファイル以外のファイル1つ分の変更前後の内容
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileChange {
+    /// ファイルの絶対パス
+    pub file: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl FileChange {
+    pub fn new(file_path: &str, before: Option<String>, after: Option<String>) -> Self {
+        FileChange {
+            file: file_key(file_path),
+            before,
+            after,
+        }
+    }
+}
+
+impl JournalEntry {
+    // タスクファイルと関連ファイルの（パス, 変更前, 変更後）
+    fn changes<'a>(
+        &'a self,
+        file_path: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a Option<String>, &'a Option<String>)> {
+        std::iter::once((file_path, &self.before, &self.after)).chain(
+            self.related
+                .iter()
+                .map(|change| (change.file.as_str(), &change.before, &change.after)),
+        )
+    }
+}
+
+/// 操作履歴。全てのタスクファイルの操作を1つのファイルに記録する
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+}
+
+// 同じタスクファイルを別の相対パスで指定しても同じ履歴になるように絶対パスにする
+fn file_key(file_path: &str) -> String {
+    std::path::absolute(file_path)
+        .unwrap_or_else(|_| PathBuf::from(file_path))
+        .display()
+        .to_string()
+}
+
+fn read_file(file_path: &str) -> Option<String> {
+    std::fs::read_to_string(file_path).ok()
+}
+
+// 内容を書き戻す（`None` はファイルを削除する）
+fn write_file(file_path: &str, contents: &Option<String>) -> std::io::Result<()> {
+    match contents {
+        Some(contents) => write_atomic(file_path, contents),
+        None => match std::fs::remove_file(file_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+impl Journal {
+    /// 履歴ファイルを読み込む（なければ空の履歴）
+    pub fn load(path: &Path) -> Result<Self, MdTaskError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| MdTaskError::Io(std::io::Error::other(e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Journal::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), MdTaskError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        write_atomic(path, &contents)?;
+        Ok(())
+    }
+
+    /// 変更操作を記録する。同じファイルの取り消し済みの操作はやり直せなくなる
+    ///
+    /// `related` には同じ操作で変更したゴミ箱ファイルやアーカイブファイルを渡す。
+    pub fn record(
+        &mut self,
+        file_path: &str,
+        command: &str,
+        before: Option<String>,
+        after: Option<String>,
+        related: Vec<FileChange>,
+    ) {
+        let file = file_key(file_path);
+        self.entries
+            .retain(|entry| !(entry.undone && entry.file == file));
+        self.entries.push(JournalEntry {
+            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            command: command.to_string(),
+            file,
+            before,
+            after,
+            related,
+            undone: false,
+        });
+
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// 指定したタスクファイルの操作（古い順）
+    pub fn entries_for(&self, file_path: &str) -> Vec<&JournalEntry> {
+        let file = file_key(file_path);
+        self.entries
+            .iter()
+            .filter(|entry| entry.file == file)
+            .collect()
+    }
+
+    /// 最後の操作を取り消してファイル（関連ファイルを含む）を変更前の内容に戻し、取り消した操作を返す
+    ///
+    /// 操作の後にいずれかのファイルが変更されている場合は、どのファイルも上書きせずにエラーにする。
+    pub fn undo(&mut self, file_path: &str) -> Result<JournalEntry, MdTaskError> {
+        let file = file_key(file_path);
+        let entry = self
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.file == file && !entry.undone)
+            .ok_or(MdTaskError::NothingToUndo)?;
+
+        for (path, _, after) in entry.changes(file_path) {
+            if read_file(path) != *after {
+                return Err(MdTaskError::FileChanged(path.to_string()));
+            }
+        }
+        for (path, before, _) in entry.changes(file_path) {
+            write_file(path, before)?;
+        }
+        entry.undone = true;
+        Ok(entry.clone())
+    }
+
+    /// 最後に取り消した操作をやり直してファイルを変更後の内容にし、やり直した操作を返す
+    pub fn redo(&mut self, file_path: &str) -> Result<JournalEntry, MdTaskError> {
+        let file = file_key(file_path);
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.file == file && entry.undone)
+            .ok_or(MdTaskError::NothingToRedo)?;
+
+        for (path, before, _) in entry.changes(file_path) {
+            if read_file(path) != *before {
+                return Err(MdTaskError::FileChanged(path.to_string()));
+            }
+        }
+        for (path, _, after) in entry.changes(file_path) {
+            write_file(path, after)?;
+        }
+        entry.undone = false;
+        Ok(entry.clone())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::MdTaskError;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// ロックが解放されるのを待つ間隔
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// タスクファイルの勧告ロック。コマンドの実行中（正規化を含む）保持し、drop で解放する
+///
+/// タスクファイル自体は書き込みのたびに置き換わるため、同じディレクトリの
+/// `.<ファイル名>.lock` をロックする。ロック中はロックしたプロセスのPIDを書いておく。
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+/// タスクファイルに対応するロックファイルのパス
+pub fn lock_path(file_path: &str) -> PathBuf {
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.lock", file_name))
+}
+
+// ロックしているプロセスのPID（読めなければ `None`）
+fn locked_by(lock_path: &Path) -> Option<u32> {
+    std::fs::read_to_string(lock_path).ok()?.trim().parse().ok()
+}
+
+impl FileLock {
+    /// ロックを取得する。他のプロセスがロックしている場合は `timeout` まで待ち、
+    /// それでも取得できなければ `FileLocked` を返す
+    pub fn acquire(file_path: &str, timeout: Duration) -> Result<FileLock, MdTaskError> {
+        let lock_path = lock_path(file_path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)?;
+
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(MdTaskError::FileLocked {
+                        file: file_path.to_string(),
+                        pid: locked_by(&lock_path),
+                    });
+                }
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        Ok(FileLock { file })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
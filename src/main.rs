@@ -1,11 +1,50 @@
 mod config;
+mod date;
+mod dedupe;
+mod diff;
+mod document;
+mod error;
+mod i18n;
+mod journal;
+mod lock;
+mod normalize;
+mod output;
+mod priority;
+mod sort;
+mod storage;
 mod task;
 
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
-use config::{load_config, show_config_paths, show_config_status};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use task::{add_task_to_file, archive_all_completed_tasks, normalize_task_file};
+use config::{
+    config_status, load_config, show_config_paths, show_config_status, validate_config_files,
+};
+use date::{DueStatus, due_status, parse_date, parse_days};
+use dedupe::DuplicateGroup;
+use diff::unified_diff;
+use document::{
+    TaskDocument, TaskItem, TaskLocation, TaskRef, TaskSelector, TaskState, is_tag_name,
+};
+use error::MdTaskError;
+use i18n::{Locale, Message, set_locale};
+use journal::{FileChange, JOURNAL_FILE, Journal};
+use lock::FileLock;
+use output::{HistoryRecord, OutputFormat, TagRecord, TaskRecord, print_records, print_value};
+use priority::set_scale;
+use sort::{SortKey, reversed_tasks, sorted_tasks};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+use storage::write_atomic;
+use task::{
+    TaskFilter, TaskOptions, TaskTargets, add_task_to_file, archive_all_completed_tasks,
+    archive_tasks, complete_tasks, count_tags, dedupe_task_file, edit_in_editor, edit_task_text,
+    empty_trash, load_archive_documents, load_document, load_trash_document, migrate_headings,
+    normalize_contents, normalize_task_file, related_files, remove_tasks, reopen_tasks,
+    restore_task, set_task_priority, set_task_state, sort_task_file,
+};
 
 /// A simple CLI tool to manage tasks in a markdown file
 #[derive(Parser, Debug)]
@@ -16,6 +55,12 @@ struct Cli {
     /// Enable verbose output
     #[arg(short, long, global = true)]
     verbose: bool,
+    /// Output format for read-only commands (list, tags, history, config show, config status)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// Abort when a config file has errors, unknown keys or invalid values
+    #[arg(long, global = true)]
+    strict_config: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -25,8 +70,37 @@ enum Commands {
     Add {
         /// The content of the task
         task: String,
-        /// Priority level (high, medium, low)
-        #[arg(short, long, default_value = "medium")]
+        /// Priority level (high, medium, low by default; see [priority] in the config).
+        /// Defaults to task_management.default_priority
+        #[arg(short, long)]
+        priority: Option<String>,
+        /// Due date (YYYY-MM-DD, today, tomorrow, +3d, next-mon, 明日, ...)
+        #[arg(long)]
+        due: Option<String>,
+        /// Tag to attach (repeatable, written as #tag)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Add as a subtask of the task with this number or ID
+        #[arg(long, value_name = "TASK")]
+        parent: Option<TaskRef>,
+    },
+    /// Change the text of a task, or open the task file in $EDITOR at the task
+    Edit {
+        /// Number or ID of the task
+        task: TaskRef,
+        /// New text of the task (the priority symbol, state and ID are kept)
+        #[arg(required_unless_present = "editor", conflicts_with = "editor")]
+        text: Option<String>,
+        /// Open $VISUAL / $EDITOR at the line of the task instead
+        #[arg(short, long)]
+        editor: bool,
+    },
+    /// Change the priority of a task
+    #[command(alias = "pri")]
+    Priority {
+        /// Number or ID of the task
+        task: TaskRef,
+        /// New priority level (high, medium, low by default; see [priority] in the config)
         priority: String,
     },
     /// List all tasks
@@ -35,28 +109,150 @@ enum Commands {
         /// Show all tasks including completed ones
         #[arg(short, long)]
         all: bool,
+        /// Only show tasks due before the given date
+        #[arg(long, value_name = "DATE")]
+        due_before: Option<String>,
+        /// Only show overdue tasks
+        #[arg(long)]
+        overdue: bool,
+        /// Only show tasks with the given tag (repeatable)
+        #[arg(short, long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Only show tasks in the given state (done and cancelled tasks are shown too)
+        #[arg(long, value_enum)]
+        state: Option<TaskState>,
+        /// Sort top-level tasks (subtasks stay under their parent)
+        #[arg(long, value_enum, value_name = "KEY")]
+        sort: Option<SortKey>,
+        /// Reverse the sort order (file order if --sort is not given)
+        #[arg(short, long)]
+        reverse: bool,
     },
+    /// List all tags with open and done task counts
+    Tags,
     /// Mark a task as done
     #[command(alias = "d")]
     Done {
-        /// The number of the task to mark as done
-        task_number: usize,
+        /// Numbers, IDs or number ranges of the tasks to mark as done (e.g. 1 3 5-7)
+        #[arg(required_unless_present = "filter")]
+        tasks: Vec<TaskSelector>,
+        /// Also mark open tasks matching this filter as done (e.g. '#work priority:low')
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
+        /// Also mark all subtasks as done
+        #[arg(short, long)]
+        cascade: bool,
+    },
+    /// Mark completed tasks as open again, moving archived ones back to the task list
+    #[command(alias = "undone")]
+    Reopen {
+        /// Numbers, IDs or number ranges of the completed tasks to reopen
+        #[arg(required_unless_present = "filter")]
+        tasks: Vec<TaskSelector>,
+        /// Also reopen completed tasks matching this filter
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
     },
-    /// Remove a task
+    /// Mark tasks as in progress (- [/])
+    Start {
+        /// Numbers, IDs or number ranges of the tasks to start
+        #[arg(required_unless_present = "filter")]
+        tasks: Vec<TaskSelector>,
+        /// Also start open tasks matching this filter
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
+    },
+    /// Mark tasks as waiting (- [?])
+    Wait {
+        /// Numbers, IDs or number ranges of the tasks to put on hold
+        #[arg(required_unless_present = "filter")]
+        tasks: Vec<TaskSelector>,
+        /// Also put open tasks matching this filter on hold
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
+    },
+    /// Mark tasks as cancelled (- [-]); they are archived like completed tasks
+    Cancel {
+        /// Numbers, IDs or number ranges of the tasks to cancel
+        #[arg(required_unless_present = "filter")]
+        tasks: Vec<TaskSelector>,
+        /// Also cancel open tasks matching this filter
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
+    },
+    /// Remove tasks
     #[command(alias = "rm")]
     Remove {
-        /// The number of the task to remove
-        task_number: usize,
+        /// Numbers, IDs or number ranges of the tasks to remove (e.g. 1 3 5-7)
+        #[arg(required_unless_present = "filter")]
+        tasks: Vec<TaskSelector>,
+        /// Also remove tasks matching this filter (e.g. '#obsolete')
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
+        /// Do not ask for confirmation when removing several tasks
+        #[arg(short, long)]
+        yes: bool,
     },
-    /// Archive a completed task
+    /// Archive completed tasks
     #[command(alias = "arc")]
     Archive {
-        /// The number of the completed task to archive
-        task_number: Option<usize>,
+        /// Numbers, IDs or number ranges of the completed tasks to archive
+        tasks: Vec<TaskSelector>,
+        /// Also archive completed tasks matching this filter
+        #[arg(long, value_name = "FILTER")]
+        filter: Option<String>,
         /// Archive all completed tasks
         #[arg(short, long)]
         all: bool,
     },
+    /// Rename section headings in the task file to the ones in [sections] config
+    MigrateHeadings {
+        /// Current title of the task list heading
+        #[arg(long, default_value = "タスク一覧")]
+        from_task: String,
+        /// Current title of the archive heading
+        #[arg(long, default_value = "アーカイブ")]
+        from_archive: String,
+    },
+    /// Find duplicate open tasks and merge them, keeping the higher priority
+    Dedupe {
+        /// Merge every group of duplicates without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Reorder the tasks in the task list section of the file
+    Sort {
+        /// Sort key
+        #[arg(long, value_enum, default_value_t = SortKey::Priority)]
+        by: SortKey,
+        /// Reverse the sort order
+        #[arg(short, long)]
+        reverse: bool,
+    },
+    /// Normalize the task file format
+    Fmt {
+        /// Exit with a non-zero code if the file is not normalized (does not write)
+        #[arg(long)]
+        check: bool,
+        /// Print a unified diff of the changes (does not write)
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Undo the last change to the task file
+    Undo,
+    /// Redo the last undone change
+    Redo,
+    /// Show recent changes to the task file
+    History {
+        /// Number of operations to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Manage removed tasks (the trash section or trash file)
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
     /// Configuration management
     Config {
         #[command(subcommand)]
@@ -64,6 +260,23 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum TrashAction {
+    /// List removed tasks with their numbers in the trash
+    List,
+    /// Move a removed task back to the task list
+    Restore {
+        /// Number in the trash (see `trash list`) or ID of the task
+        task: TaskRef,
+    },
+    /// Permanently delete removed tasks
+    Empty {
+        /// Only delete tasks removed more than this long ago (e.g. 30d, 2w)
+        #[arg(long, value_name = "PERIOD")]
+        older_than: Option<String>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum ConfigAction {
     /// Install global configuration (run once after installation)
@@ -76,11 +289,166 @@ enum ConfigAction {
     Path,
     /// Show comprehensive configuration status
     Status,
+    /// Check every config file for syntax errors, unknown keys and invalid values
+    Validate,
+}
+
+// 操作履歴は全てのタスクファイルで共有するため、読み込みから保存までを別にロックする
+fn lock_journal(journal_path: &Path, timeout: Duration) -> Result<FileLock, MdTaskError> {
+    if let Some(dir) = journal_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    FileLock::acquire(&journal_path.to_string_lossy(), timeout)
+}
+
+// タスクファイルを変更するコマンド（操作履歴に記録し、undoで取り消せる）
+fn is_mutating(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Add { .. }
+            | Commands::Edit { .. }
+            | Commands::Priority { .. }
+            | Commands::Done { .. }
+            | Commands::Reopen { .. }
+            | Commands::Start { .. }
+            | Commands::Wait { .. }
+            | Commands::Cancel { .. }
+            | Commands::Remove { .. }
+            | Commands::Archive { .. }
+            | Commands::MigrateHeadings { .. }
+            | Commands::Dedupe { .. }
+            | Commands::Sort { .. }
+            | Commands::Fmt { .. }
+            | Commands::Trash {
+                action: TrashAction::Restore { .. } | TrashAction::Empty { .. }
+            }
+    )
+}
+
+// 引数のタスクの指定と `--filter` の条件式から一括操作の対象を作る
+fn task_targets(
+    selectors: Vec<TaskSelector>,
+    filter: Option<String>,
+) -> Result<TaskTargets, MdTaskError> {
+    let (filter, filter_expr) = match filter {
+        Some(expr) => (Some(TaskFilter::parse(&expr, date::today())?), expr),
+        None => (None, String::new()),
+    };
+    Ok(TaskTargets {
+        selectors,
+        filter,
+        filter_expr,
+    })
+}
+
+// 一括操作のメッセージ用のタスク番号の一覧（例: `1, 3, 5`）
+fn join_numbers(numbers: &[usize]) -> String {
+    numbers
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// 状態を変更したタスクの表示（1つだけ指定した場合は指定した番号・ID）
+fn changed_tasks(targets: &TaskTargets, numbers: &[usize]) -> String {
+    if targets.is_bulk() {
+        join_numbers(numbers)
+    } else {
+        targets.selectors[0].to_string()
+    }
+}
+
+// 使用するエディタ（`$VISUAL`、`$EDITOR` の順。どちらもなければ `vi`）
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+// 一覧の状態の表示
+fn state_message(state: TaskState) -> Message<'static> {
+    match state {
+        TaskState::Open => Message::StateOpen,
+        TaskState::InProgress => Message::StateInProgress,
+        TaskState::Waiting => Message::StateWaiting,
+        TaskState::Done => Message::StateDone,
+        TaskState::Cancelled => Message::StateCancelled,
+    }
+}
+
+// 確認のプロンプトを表示し、`y` / `yes` が入力されたら `true`（入力がなければ `false`）
+fn confirm(prompt: &str) -> bool {
+    print!("{}", prompt);
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    match std::io::stdin().read_line(&mut answer) {
+        Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
+
+// 一覧表示用のタスク番号とID（例: `3 [7f3a]`）
+fn label(location: &TaskLocation, task: &TaskItem) -> String {
+    match task.id() {
+        Some(id) => format!("{} [{}]", location.number, id),
+        None => location.number.to_string(),
+    }
 }
 
-fn main() -> std::io::Result<()> {
+// アーカイブファイルのタスクは番号で指定できないため、IDだけを表示する（例: `[7f3a]`）
+fn archived_label(task: &TaskItem) -> String {
+    format!("[{}]", task.id().unwrap_or("-"))
+}
+
+// 一覧表示用の行（サブタスクのインデント、本文、サブタスクの進捗、期日の状態）
+fn describe(document: &TaskDocument, location: &TaskLocation, today: NaiveDate) -> String {
+    let label = label(location, document.task(location));
+    describe_with_label(document, location, &label, today)
+}
+
+fn describe_with_label(
+    document: &TaskDocument,
+    location: &TaskLocation,
+    label: &str,
+    today: NaiveDate,
+) -> String {
+    let task = document.task(location);
+    let mut line = format!("{}{}: {}", "  ".repeat(location.depth), label, task.body());
+
+    let (done, total) = document.progress(location);
+    if total > 0 {
+        line = format!("{} [{}/{}]", line, done, total);
+    }
+
+    if let Some(due) = task.due()
+        && !task.is_closed()
+    {
+        let marker = match due_status(due, today) {
+            DueStatus::Overdue(days) => Message::Overdue(days),
+            DueStatus::Today => Message::DueToday,
+            DueStatus::Upcoming(days) => Message::DueIn(days),
+        };
+        line = format!("{} {}", line, marker);
+    }
+    line
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
 
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), MdTaskError> {
     // verboseフラグが指定された場合は環境変数を設定
     if cli.verbose {
         unsafe {
@@ -88,232 +456,586 @@ fn main() -> std::io::Result<()> {
         }
     }
 
-    let config = load_config();
+    // 厳格モードも設定の読み込み前に環境変数で伝える
+    if cli.strict_config {
+        unsafe {
+            std::env::set_var("MD_TASK_STRICT_CONFIG", "1");
+        }
+    }
+
+    // 設定ファイルの検査は他の設定に依存しないため、読み込み前に実行する
+    if let Commands::Config {
+        action: ConfigAction::Validate,
+    } = cli.command
+    {
+        return match validate_config_files() {
+            0 => Ok(()),
+            problems => Err(MdTaskError::InvalidConfig(problems)),
+        };
+    }
+
+    let config = load_config()?;
+    set_locale(Locale::from_setting(&config.display.locale));
+    set_scale(config.priority.clone());
     let task_file_path = &config.file_paths.task_file;
+    let format = cli.format;
+    let journal_path = config::get_config_dir().join(JOURNAL_FILE);
+    let lock_timeout = Duration::from_secs(config.task_management.lock_timeout_secs);
+
+    // 他のプロセスと同時に書き込まないよう、正規化を含むコマンド全体でタスクファイルをロックする
+    let _lock = match cli.command {
+        Commands::Config { .. } => None,
+        _ => Some(FileLock::acquire(task_file_path, lock_timeout)?),
+    };
+
+    // 変更系コマンドは取り消せるように実行前の内容を控えておく（アーカイブファイルなども含む）
+    let before = is_mutating(&cli.command).then(|| std::fs::read_to_string(task_file_path).ok());
+    let related_before: BTreeMap<String, Option<String>> = if before.is_some() {
+        related_files(task_file_path, &config)?
+            .into_iter()
+            .map(|path| {
+                let contents = std::fs::read_to_string(&path).ok();
+                (path, contents)
+            })
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
 
     match cli.command {
-        Commands::Add { task, priority } => {
-            // 優先度の検証
-            let valid_priorities = ["high", "medium", "low"];
-            if !valid_priorities.contains(&priority.as_str()) {
-                println!(
-                    "ERROR: Invalid priority '{}'. Use: high, medium, or low",
-                    priority
-                );
-                return Ok(());
+        Commands::Add {
+            task,
+            priority,
+            due,
+            tags,
+            parent,
+        } => {
+            // 優先度の検証（省略時は設定の default_priority）
+            let priority =
+                priority.unwrap_or_else(|| config.task_management.default_priority.clone());
+            if config.priority.level(&priority).is_none() {
+                return Err(MdTaskError::InvalidPriority(priority));
+            }
+
+            // 期日の解析（相対指定はここで絶対日付に変換する）
+            let due = due
+                .map(|due| {
+                    parse_date(&due, date::today()).map_err(|_| MdTaskError::InvalidDate(due))
+                })
+                .transpose()?;
+
+            // タグの検証（先頭の # は省略可能）
+            let tags: Vec<String> = tags
+                .iter()
+                .map(|tag| tag.trim_start_matches('#').to_string())
+                .collect();
+            if let Some(tag) = tags.iter().find(|tag| !is_tag_name(tag)) {
+                return Err(MdTaskError::InvalidTag(tag.clone()));
             }
 
-            add_task_to_file(task_file_path, &task, &priority)?;
-            println!("Task added: {} ({} priority)", task, priority);
+            let options = TaskOptions { due, tags, parent };
+            let id = add_task_to_file(task_file_path, &config, &task, &priority, &options)?;
+            println!(
+                "{}",
+                Message::TaskAdded {
+                    task: &task,
+                    priority: &priority,
+                    id: &id,
+                }
+            );
+        }
+        Commands::Edit { task, text, editor } => {
+            match text {
+                Some(text) if !editor => {
+                    edit_task_text(task_file_path, &config, &task, &text)?;
+                }
+                _ => {
+                    // 編集後のファイルを検査し、タスクとして読み込めない行を警告する
+                    let editor = editor_command();
+                    for line in edit_in_editor(task_file_path, &config, &task, &editor)? {
+                        eprintln!("{}", Message::MalformedTaskLine(line));
+                    }
+                }
+            }
+            println!("{}", Message::TaskEdited(&task.to_string()));
+        }
+        Commands::Priority { task, priority } => {
+            set_task_priority(task_file_path, &config, &task, &priority)?;
+            println!(
+                "{}",
+                Message::PriorityChanged {
+                    task: &task.to_string(),
+                    priority: &priority,
+                }
+            );
         }
-        Commands::List { all } => {
-            // --- ファイル読み込み処理 ---
+        Commands::List {
+            all,
+            due_before,
+            overdue,
+            tags,
+            state,
+            sort,
+            reverse,
+        } => {
+            let today = date::today();
+            let due_before = due_before
+                .map(|d| parse_date(&d, today).map_err(|_| MdTaskError::InvalidDate(d)))
+                .transpose()?;
+            let filter = TaskFilter {
+                due_before,
+                overdue,
+                tags,
+                state,
+                ..Default::default()
+            };
+            // 状態を指定した場合は完了済み・中止のタスクも表示する
+            let show_closed = all || state.is_some();
 
-            // 1. ファイルを開く(task.md)
-            let file = match File::open(task_file_path) {
-                // 読み込み専用で開く
-                Ok(file) => file, // ファイルが存在する場合はそのファイルを使用
+            // タスクファイルを読み込む（存在しない場合は何もせずに終了）
+            let document = match load_document(task_file_path, &config) {
+                Ok(document) => document,
                 Err(_) => {
-                    println!("No tasks found. Please add a task first.");
-                    return Ok(()); // ファイルが存在しない場合は、何もせずに終了
+                    println!("{}", Message::NoTasks);
+                    return Ok(());
                 }
             };
 
-            // 2. ファイルを1行ずつ読み込む
-            let reader = BufReader::new(file); // ファイルをバッファリングして
+            // 表示順（--reverse だけの場合はファイル内の順番を逆にする）
+            let locations = match sort {
+                Some(key) => sorted_tasks(&document, key, reverse),
+                None if reverse => reversed_tasks(&document),
+                None => document.tasks(),
+            };
 
-            if all {
+            // アーカイブファイルのタスク（完了済みを表示する場合のみ、ファイルの新しい順）
+            let archives = if show_closed {
+                load_archive_documents(task_file_path, &config)?
+            } else {
+                Vec::new()
+            };
+            let archived: Vec<(&str, &TaskDocument, TaskLocation)> = archives
+                .iter()
+                .flat_map(|(path, archive)| {
+                    archive
+                        .tasks()
+                        .into_iter()
+                        .filter(|location| filter.matches(archive.task(location), today))
+                        .map(move |location| (path.as_str(), archive, location))
+                })
+                .collect();
+
+            if format != OutputFormat::Text {
+                // 機械可読な形式で出力
+                let mut records: Vec<TaskRecord> = locations
+                    .iter()
+                    .filter(|location| {
+                        let task = document.task(location);
+                        (show_closed || !task.is_closed()) && filter.matches(task, today)
+                    })
+                    .map(|location| TaskRecord::new(&document, location))
+                    .collect();
+                records.extend(archived.iter().map(|(path, archive, location)| {
+                    TaskRecord::archived(path, archive, location)
+                }));
+                print_records(format, &records);
+            } else if all {
                 // 全てのタスクを表示（完了済みも含む）
-                println!("--- All Tasks ---");
+                println!("{}", Message::AllTasksHeader);
                 let mut incomplete_count = 0;
                 let mut complete_count = 0;
                 let mut archived_count = 0;
                 let mut in_archive_section = false;
 
-                reader.lines()
-                    .map_while(Result::ok) // エラーのない行だけを取り出す
-                    .for_each(|line| {
-                        // アーカイブセクションの開始を検知
-                        if line == "## アーカイブ" {
-                            in_archive_section = true;
-                            if incomplete_count > 0 || complete_count > 0 {
-                                println!(); // セクション間に空行を追加
-                            }
-                            println!("--- アーカイブ済み ---");
-                            return;
+                for location in locations {
+                    let task = document.task(&location);
+                    if !filter.matches(task, today) {
+                        continue;
+                    }
+
+                    // アーカイブセクションの開始を検知
+                    if !in_archive_section && document.is_archive(location.section) {
+                        in_archive_section = true;
+                        if incomplete_count > 0 || complete_count > 0 {
+                            println!(); // セクション間に空行を追加
                         }
+                        println!("{}", Message::ArchivedHeader);
+                    }
 
-                        if line.starts_with("- [ ]") {
-                            incomplete_count += 1;
-                            let task_content = &line[6..]; // "- [ ] "を除去
-                            println!("{}: {} (未完了)", incomplete_count, task_content);
-                        } else if line.starts_with("- [x]") {
-                            if in_archive_section {
-                                archived_count += 1;
-                                let task_content = &line[6..]; // "- [x] "を除去
-                                println!("A{}: {} (アーカイブ済み)", archived_count, task_content);
-                            } else {
-                                complete_count += 1;
-                                let task_content = &line[6..]; // "- [x] "を除去
-                                println!("✓: {} (完了済み)", task_content);
-                            }
+                    let status = if !task.is_closed() {
+                        incomplete_count += 1;
+                        state_message(task.state)
+                    } else if in_archive_section {
+                        archived_count += 1;
+                        Message::StateArchived
+                    } else {
+                        complete_count += 1;
+                        state_message(task.state)
+                    };
+                    println!("{} ({})", describe(&document, &location, today), status);
+                }
+
+                for (_, archive, location) in &archived {
+                    if !in_archive_section {
+                        in_archive_section = true;
+                        if incomplete_count > 0 || complete_count > 0 {
+                            println!();
                         }
-                    });
+                        println!("{}", Message::ArchivedHeader);
+                    }
+                    archived_count += 1;
+                    let label = archived_label(archive.task(location));
+                    let line = describe_with_label(archive, location, &label, today);
+                    println!("{} ({})", line, Message::StateArchived);
+                }
 
                 println!(
-                    "\n合計: 未完了 {}件, 完了済み {}件, アーカイブ済み {}件",
-                    incomplete_count, complete_count, archived_count
+                    "\n{}",
+                    Message::ListSummary {
+                        open: incomplete_count,
+                        done: complete_count,
+                        archived: archived_count,
+                    }
                 );
             } else {
-                // 未完了タスクのみ表示（従来の動作）
-                println!("--- Tasks ---");
-                reader.lines()
-                    .map_while(Result::ok) // エラーのない行だけを取り出す
-                    .filter(|line| line.starts_with("- [ ]")) // 未完了タスクのみをフィルタリング
-                    .enumerate() // 行番号を付ける
-                    .for_each(|(index, task_line)| {
-                        let task_content = &task_line[6..]; // "- [ ] "を除去
-                        println!("{}: {}", index + 1, task_content); // インデックスを1から始めて表示
-                    });
-            }
-        }
-        Commands::Done { task_number } => {
-            // 1. ファイルを文字列として丸ごと読み込む
-            let contents = std::fs::read_to_string(task_file_path)?;
-
-            // 2. 未完了タスクを数えながら、指定された番号のタスクを書き換える
-            let mut task_count = 0;
-            let mut task_found = false;
-            let new_contents: String = contents.lines()
-                .map(|line| {
-                    if line.starts_with("- [ ]") {
-                        task_count += 1; // 未完了タスクのカウントを増やす
-                        if task_count == task_number { // 指定されたタスク番号と一致する場合
-                            task_found = true; // タスクが見つかったフラグを立てる
-                            return line.replace("- [ ]", "- [x]"); // タスクを完了に変更
+                // 未完了タスクのみ表示（従来の動作）。進行中・保留などは状態を添える
+                println!("{}", Message::TasksHeader);
+                for location in locations {
+                    let task = document.task(&location);
+                    if (show_closed || !task.is_closed()) && filter.matches(task, today) {
+                        let line = describe(&document, &location, today);
+                        match task.state {
+                            TaskState::Open => println!("{}", line),
+                            state => println!("{} ({})", line, state_message(state)),
                         }
                     }
-                    // 対象外の行はそのまま返す
-                    line.to_string()
-                })
-                .collect::<Vec<String>>() // 変換結果をベクタに収集
-                .join("\n"); // 行を改行で結合して新しい文字列
+                }
+                for (_, archive, location) in &archived {
+                    let task = archive.task(location);
+                    let line = describe_with_label(archive, location, &archived_label(task), today);
+                    println!("{} ({})", line, state_message(task.state));
+                }
+            }
+        }
+        Commands::Tags => {
+            let document = match load_document(task_file_path, &config) {
+                Ok(document) => document,
+                Err(_) => {
+                    println!("{}", Message::NoTasks);
+                    return Ok(());
+                }
+            };
 
-            // 3. 変更後の内容でファイルを上書き保存する
-            if task_found {
-                std::fs::write(task_file_path, new_contents)?; // ファイルに書き込む
-                println!("Task {} marked as done.", task_number); // 成功メッセージ
+            let archives = load_archive_documents(task_file_path, &config)?;
+            let counts = count_tags(
+                std::iter::once(&document).chain(archives.iter().map(|(_, archive)| archive)),
+            );
+            if format != OutputFormat::Text {
+                let records: Vec<TagRecord> = counts
+                    .into_iter()
+                    .map(|(tag, (open, done))| TagRecord { tag, open, done })
+                    .collect();
+                print_records(format, &records);
+            } else {
+                println!("{}", Message::TagsHeader);
+                for (tag, (open, done)) in counts {
+                    println!(
+                        "{}",
+                        Message::TagSummary {
+                            tag: &tag,
+                            open,
+                            done
+                        }
+                    );
+                }
+            }
+        }
+        Commands::Done {
+            tasks,
+            filter,
+            cascade,
+        } => {
+            let targets = task_targets(tasks, filter)?;
+            let done = complete_tasks(task_file_path, &config, &targets, cascade)?;
+            if targets.is_bulk() {
+                println!("{}", Message::TasksDone(&join_numbers(&done)));
+            } else {
+                println!("{}", Message::TaskDone(&targets.selectors[0].to_string())); // 成功メッセージ
+            }
             // TODO: 設定で`DONE`コマンドの最後に`list`コマンドを実行するようにするか選択できるようにする`
+        }
+        Commands::Reopen { tasks, filter } => {
+            let targets = task_targets(tasks, filter)?;
+            let reopened = reopen_tasks(task_file_path, &config, &targets)?;
+            if targets.is_bulk() {
+                println!("{}", Message::TasksReopened(&join_numbers(&reopened)));
             } else {
-                println!("ERROR: Task number {} not found.", task_number); // タスクが見つからなかった場合のメッセージ
+                println!(
+                    "{}",
+                    Message::TaskReopened(&targets.selectors[0].to_string())
+                );
             }
         }
-        Commands::Remove { task_number } => {
-            // 1. ファイルを文字列として丸ごと読み込む
-            let contents = std::fs::read_to_string(task_file_path)?;
-
-            // 2. 未完了タスクを数えながら、指定された番号のタスクを削除する
-            let mut task_count = 0;
-            let new_contents: String = contents.lines()
-                .filter_map(|line| {
-                    if line.starts_with("- [ ]") {
-                        task_count += 1; // 未完了タスクのカウントを増やす
-                        if task_count == task_number { // 指定されたタスク番号と一致する場合
-                            return None; // この行は削除するのでNoneを返す
-                        }
-                    }
-                    Some(line.to_string()) // 対象外の行はそのまま返す
-                })
-                .collect::<Vec<String>>() // 変換結果をベクタに収集
-                .join("\n"); // 行を改行で結合して新しい文字列
-
-            // 3. 変更後の内容でファイルを上書き保存する
-            std::fs::write(task_file_path, new_contents)?; // ファイルに書き込む
-            println!("Task {} removed.", task_number); // 成功メッセージ
+        Commands::Start { tasks, filter } => {
+            let targets = task_targets(tasks, filter)?;
+            let started = set_task_state(task_file_path, &config, &targets, TaskState::InProgress)?;
+            println!(
+                "{}",
+                Message::TasksStarted(&changed_tasks(&targets, &started))
+            );
         }
-        Commands::Archive { task_number, all } => {
-            if all {
-                // 全ての完了済みタスクをアーカイブ
-                archive_all_completed_tasks(task_file_path)?;
-                println!("All completed tasks have been archived.");
-            } else if let Some(task_num) = task_number {
-                // 指定された番号の完了済みタスクをアーカイブ
-                // 1. ファイルを文字列として丸ごと読み込む
-                let contents = std::fs::read_to_string(task_file_path)?;
-
-                // 2. 完了済みタスクを数えながら、指定された番号のタスクを見つける
-                let mut completed_task_count = 0;
-                let mut task_found = false;
-                let mut archived_task = String::new();
-                let mut lines: Vec<String> = Vec::new();
-                let mut archive_section_exists = false;
-                let mut archive_section_start = 0;
-
-                // まず、完了済みタスクを探してアーカイブ対象を特定
-                for line in contents.lines() {
-                    if line == "## アーカイブ" {
-                        archive_section_exists = true;
-                        archive_section_start = lines.len();
-                    }
-
-                    if line.starts_with("- [x]") {
-                        completed_task_count += 1;
-                        if completed_task_count == task_num {
-                            task_found = true;
-                            archived_task = line.to_string();
-                            continue; // この行は除外
-                        }
-                    }
-                    lines.push(line.to_string());
+        Commands::Wait { tasks, filter } => {
+            let targets = task_targets(tasks, filter)?;
+            let waiting = set_task_state(task_file_path, &config, &targets, TaskState::Waiting)?;
+            println!(
+                "{}",
+                Message::TasksWaiting(&changed_tasks(&targets, &waiting))
+            );
+        }
+        Commands::Cancel { tasks, filter } => {
+            let targets = task_targets(tasks, filter)?;
+            let cancelled =
+                set_task_state(task_file_path, &config, &targets, TaskState::Cancelled)?;
+            println!(
+                "{}",
+                Message::TasksCancelled(&changed_tasks(&targets, &cancelled))
+            );
+        }
+        Commands::Remove { tasks, filter, yes } => {
+            let targets = task_targets(tasks, filter)?;
+            // 複数のタスクを削除する場合は対象を表示して確認する
+            let confirm_removal = |document: &TaskDocument, locations: &[TaskLocation]| {
+                if yes || !targets.is_bulk() {
+                    return true;
                 }
-
-                if !task_found {
-                    println!("ERROR: Completed task number {} not found.", task_num);
-                    return Ok(());
+                let today = date::today();
+                for location in locations {
+                    println!("{}", describe(document, location, today));
                 }
-
-                // 3. アーカイブセクションを追加または既存セクションに追記
-                if !archive_section_exists {
-                    // アーカイブセクションが存在しない場合は新しく作成
-                    lines.push("".to_string()); // 空行
-                    lines.push("## アーカイブ".to_string());
-                    lines.push("".to_string()); // 空行
-                    lines.push(archived_task);
+                confirm(&Message::ConfirmRemove(locations.len()).to_string())
+            };
+            let removed = remove_tasks(task_file_path, &config, &targets, confirm_removal)?;
+            if targets.is_bulk() {
+                println!("{}", Message::TasksRemoved(&join_numbers(&removed)));
+            } else {
+                println!(
+                    "{}",
+                    Message::TaskRemoved(&targets.selectors[0].to_string())
+                );
+            }
+        }
+        Commands::Dedupe { yes } => {
+            // 重複ごとに残すタスクに `*` を付けて表示し、確認する
+            let confirm_merge = |document: &TaskDocument, group: &DuplicateGroup| {
+                let today = date::today();
+                let header = if group.exact {
+                    Message::DuplicatesExact
                 } else {
-                    // 既存のアーカイブセクションに追記
-                    lines.insert(archive_section_start + 1, "".to_string()); // セクションタイトルの後に空行
-                    lines.insert(archive_section_start + 2, archived_task);
+                    Message::DuplicatesSimilar
+                };
+                println!("{}", header);
+                let mut locations: Vec<&TaskLocation> =
+                    std::iter::once(&group.keep).chain(&group.merge).collect();
+                locations.sort_by_key(|location| location.number);
+                for location in locations {
+                    let mark = if *location == group.keep { '*' } else { ' ' };
+                    println!("{} {}", mark, describe(document, location, today));
+                }
+                yes || confirm(&Message::ConfirmMerge.to_string())
+            };
+            let (found, merged) = dedupe_task_file(task_file_path, &config, confirm_merge)?;
+            if found == 0 {
+                println!("{}", Message::NoDuplicates);
+            } else {
+                println!("{}", Message::DuplicatesMerged(merged));
+            }
+        }
+        Commands::Archive { tasks, filter, all } => {
+            if all {
+                // 全ての完了済みタスクをアーカイブ
+                archive_all_completed_tasks(task_file_path, &config)?;
+                println!("{}", Message::AllArchived);
+            } else if !tasks.is_empty() || filter.is_some() {
+                // 指定された番号・ID・範囲、または条件に一致する完了済みタスクをアーカイブ
+                let targets = task_targets(tasks, filter)?;
+                let archived = archive_tasks(task_file_path, &config, &targets)?;
+                if targets.is_bulk() {
+                    println!("{}", Message::TasksArchived(&join_numbers(&archived)));
+                } else {
+                    println!(
+                        "{}",
+                        Message::TaskArchived(&targets.selectors[0].to_string())
+                    );
                 }
-
-                // 4. 変更後の内容でファイルを上書き保存
-                let new_contents = lines.join("\n");
-                std::fs::write(task_file_path, new_contents)?;
-                println!("Task {} archived successfully.", task_num);
             } else {
-                println!("ERROR: Please specify either --all or a task number.");
+                return Err(MdTaskError::ArchiveTargetMissing);
             }
         }
+        Commands::MigrateHeadings {
+            from_task,
+            from_archive,
+        } => {
+            let renamed = migrate_headings(task_file_path, &config, &from_task, &from_archive)?;
+            println!("{}", Message::HeadingsRenamed(renamed));
+        }
+        Commands::Sort { by, reverse } => {
+            let sorted = sort_task_file(task_file_path, &config, by, reverse)?;
+            println!("{}", Message::TasksSorted(sorted));
+        }
+        Commands::Trash { action } => match action {
+            TrashAction::List => {
+                let document = match load_trash_document(task_file_path, &config) {
+                    Ok((_, document)) => document,
+                    Err(_) => {
+                        println!("{}", Message::TrashEmpty);
+                        return Ok(());
+                    }
+                };
+                let locations = document.trashed_tasks();
+                if format != OutputFormat::Text {
+                    let records: Vec<TaskRecord> = locations
+                        .iter()
+                        .map(|location| TaskRecord::new(&document, location))
+                        .collect();
+                    print_records(format, &records);
+                } else if locations.is_empty() {
+                    println!("{}", Message::TrashEmpty);
+                } else {
+                    println!("{}", Message::TrashHeader);
+                    let today = date::today();
+                    for location in locations {
+                        let line = describe(&document, &location, today);
+                        match document.task(&location).meta("removed") {
+                            Some(removed) => println!("{} ({})", line, Message::RemovedOn(removed)),
+                            None => println!("{}", line),
+                        }
+                    }
+                }
+            }
+            TrashAction::Restore { task } => {
+                restore_task(task_file_path, &config, &task)?;
+                println!("{}", Message::TaskRestored(&task.to_string()));
+            }
+            TrashAction::Empty { older_than } => {
+                let older_than = older_than
+                    .map(|period| {
+                        parse_days(&period).map_err(|_| MdTaskError::InvalidPeriod(period))
+                    })
+                    .transpose()?;
+                let deleted = empty_trash(task_file_path, &config, older_than)?;
+                println!("{}", Message::TrashEmptied(deleted));
+            }
+        },
         Commands::Config { action } => match action {
             ConfigAction::Install => {
-                if let Err(e) = config::install_global_config() {
-                    println!("Error installing global config: {}", e);
-                }
+                config::install_global_config()
+                    .map_err(|e| MdTaskError::InstallConfigFailed(e.to_string()))?;
             }
             ConfigAction::Init => {
-                if let Err(e) = config::create_local_config() {
-                    println!("Error creating config file: {}", e);
-                }
+                config::create_local_config()
+                    .map_err(|e| MdTaskError::CreateConfigFailed(e.to_string()))?;
             }
             ConfigAction::Show => {
-                println!("{:#?}", config);
+                if format != OutputFormat::Text {
+                    print_value(format, &config);
+                } else {
+                    println!("{:#?}", config);
+                }
             }
             ConfigAction::Path => {
                 show_config_paths();
             }
             ConfigAction::Status => {
-                show_config_status();
+                if format != OutputFormat::Text {
+                    print_value(format, &config_status()?);
+                } else {
+                    show_config_status()?;
+                }
             }
+            ConfigAction::Validate => unreachable!("config validate is handled before loading"),
         },
+        Commands::Fmt { check, diff } => {
+            let contents = match std::fs::read_to_string(task_file_path) {
+                Ok(contents) => contents,
+                Err(_) => {
+                    println!("{}", Message::NoTasks);
+                    return Ok(());
+                }
+            };
+            let normalized = normalize_contents(task_file_path, &contents, &config)?;
+
+            // --check / --diff はファイルを書き換えないため、自動正規化もせずに終了する
+            if check || diff {
+                if diff {
+                    print!(
+                        "{}",
+                        unified_diff(
+                            &contents,
+                            &normalized,
+                            &format!("a/{}", task_file_path),
+                            &format!("b/{}", task_file_path),
+                        )
+                    );
+                }
+                if check && normalized != contents {
+                    return Err(MdTaskError::NotFormatted(task_file_path.clone()));
+                }
+                return Ok(());
+            }
+
+            if normalized != contents {
+                write_atomic(task_file_path, &normalized)?;
+                println!("{}", Message::Formatted(task_file_path));
+            } else {
+                println!("{}", Message::AlreadyFormatted(task_file_path));
+            }
+        }
+        // undo / redo は記録された内容をそのまま書き戻すため、正規化せずに終了する
+        Commands::Undo => {
+            let _journal_lock = lock_journal(&journal_path, lock_timeout)?;
+            let mut journal = Journal::load(&journal_path)?;
+            let entry = journal.undo(task_file_path)?;
+            journal.save(&journal_path)?;
+            println!("{}", Message::Undone(&entry.command));
+            return Ok(());
+        }
+        Commands::Redo => {
+            let _journal_lock = lock_journal(&journal_path, lock_timeout)?;
+            let mut journal = Journal::load(&journal_path)?;
+            let entry = journal.redo(task_file_path)?;
+            journal.save(&journal_path)?;
+            println!("{}", Message::Redone(&entry.command));
+            return Ok(());
+        }
+        Commands::History { limit } => {
+            let journal = Journal::load(&journal_path)?;
+            let entries = journal.entries_for(task_file_path);
+            let recent = &entries[entries.len().saturating_sub(limit)..];
+
+            if format != OutputFormat::Text {
+                let records: Vec<HistoryRecord> = recent
+                    .iter()
+                    .map(|entry| HistoryRecord {
+                        timestamp: entry.timestamp.clone(),
+                        command: entry.command.clone(),
+                        undone: entry.undone,
+                    })
+                    .collect();
+                print_records(format, &records);
+            } else if recent.is_empty() {
+                println!("{}", Message::NoHistory);
+            } else {
+                println!("{}", Message::HistoryHeader);
+                for entry in recent {
+                    let undone = if entry.undone {
+                        format!(" {}", Message::HistoryUndone)
+                    } else {
+                        String::new()
+                    };
+                    println!("{}  {}{}", entry.timestamp, entry.command, undone);
+                }
+            }
+        }
     }
 
     // 全てのコマンド実行後にファイル形式を正規化
@@ -321,5 +1043,30 @@ fn main() -> std::io::Result<()> {
         normalize_task_file(task_file_path, &config)?;
     }
 
+    // 変更があれば操作履歴に記録する
+    if let Some(before) = before {
+        let after = std::fs::read_to_string(task_file_path).ok();
+        // コマンドの実行中に作られたファイル（新しい月のアーカイブファイルなど）は変更前がない
+        let mut paths: Vec<String> = related_files(task_file_path, &config)?;
+        paths.extend(related_before.keys().cloned());
+        paths.sort();
+        paths.dedup();
+        let related: Vec<FileChange> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let before = related_before.get(&path).cloned().flatten();
+                let after = std::fs::read_to_string(&path).ok();
+                (after != before).then(|| FileChange::new(&path, before, after))
+            })
+            .collect();
+        if after != before || !related.is_empty() {
+            let command: Vec<String> = std::env::args().skip(1).collect();
+            let _journal_lock = lock_journal(&journal_path, lock_timeout)?;
+            let mut journal = Journal::load(&journal_path)?;
+            journal.record(task_file_path, &command.join(" "), before, after, related);
+            journal.save(&journal_path)?;
+        }
+    }
+
     Ok(())
 }
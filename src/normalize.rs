@@ -0,0 +1,210 @@
+use crate::config::Config;
+use crate::document::{Node, Section, TaskDocument, TaskLocation};
+use crate::priority;
+use crate::sort::{SortKey, sort_section};
+
+/// 正規化ルール。`rules()` の順に、設定で有効なものだけが適用される
+pub trait NormalizeRule {
+    /// ルール名（`[normalize]` のキー）
+    fn name(&self) -> &'static str;
+    /// 設定で有効になっているか
+    fn is_enabled(&self, config: &Config) -> bool;
+    fn apply(&self, document: &mut TaskDocument, config: &Config);
+}
+
+/// 先頭にタスク一覧の見出しがない場合は追加する
+pub struct TaskHeading;
+
+impl NormalizeRule for TaskHeading {
+    fn name(&self) -> &'static str {
+        "task_heading"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.task_heading
+    }
+
+    fn apply(&self, document: &mut TaskDocument, _config: &Config) {
+        let task_heading = document.headings.task.clone();
+        match document.sections.first_mut() {
+            Some(section) if section.heading.is_none() => {
+                section.heading = Some(task_heading);
+                section.nodes.insert(0, Node::Text(String::new()));
+            }
+            Some(section) if section.is(&task_heading) => {}
+            _ => document
+                .sections
+                .insert(0, Section::with_blank_line(&task_heading)),
+        }
+    }
+}
+
+/// 連続する空行を1行にまとめる
+pub struct CollapseBlankLines;
+
+impl NormalizeRule for CollapseBlankLines {
+    fn name(&self) -> &'static str {
+        "collapse_blank_lines"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.collapse_blank_lines
+    }
+
+    fn apply(&self, document: &mut TaskDocument, _config: &Config) {
+        for section in &mut document.sections {
+            let mut prev_empty = false;
+            section.nodes.retain(|node| {
+                let is_empty = node.is_blank();
+                let keep = !(is_empty && prev_empty);
+                prev_empty = is_empty;
+                keep
+            });
+        }
+    }
+}
+
+/// 優先度記号がないタスクに `task_management.default_priority` の記号を追加する
+pub struct DefaultPriority;
+
+impl NormalizeRule for DefaultPriority {
+    fn name(&self) -> &'static str {
+        "default_priority"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.default_priority
+    }
+
+    fn apply(&self, document: &mut TaskDocument, config: &Config) {
+        // 段階にない default_priority は設定の読み込み時に報告済みなので何もしない
+        let Some(symbol) = priority::scale().symbol(&config.task_management.default_priority)
+        else {
+            return;
+        };
+        for section in &mut document.sections {
+            for node in &mut section.nodes {
+                if let Node::Task(task) = node
+                    && task.priority_symbol().is_none()
+                {
+                    task.content = format!("{} {}", symbol, task.content);
+                }
+            }
+        }
+    }
+}
+
+/// アーカイブ内の未完了タスク（完了済み・中止以外）をサブタスクごとタスク一覧に戻す
+///
+/// `task_management.allow_incomplete_in_archive` が有効な場合も適用しない。
+pub struct UnarchiveIncomplete;
+
+impl NormalizeRule for UnarchiveIncomplete {
+    fn name(&self) -> &'static str {
+        "unarchive_incomplete"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.unarchive_incomplete && !config.task_management.allow_incomplete_in_archive
+    }
+
+    fn apply(&self, document: &mut TaskDocument, _config: &Config) {
+        let locations: Vec<TaskLocation> = document
+            .tasks()
+            .into_iter()
+            .filter(|location| {
+                location.depth == 0
+                    && document.is_archive(location.section)
+                    && !document.task(location).is_closed()
+            })
+            .collect();
+
+        if !locations.is_empty() {
+            let blocks = document.remove_blocks(&locations);
+            let task_index = document.task_section_index();
+            document.sections[task_index].push_nodes(blocks);
+        }
+    }
+}
+
+/// IDのないタスク（またはIDが重複したタスク）にIDを付与する
+pub struct AssignIds;
+
+impl NormalizeRule for AssignIds {
+    fn name(&self) -> &'static str {
+        "assign_ids"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.assign_ids
+    }
+
+    fn apply(&self, document: &mut TaskDocument, _config: &Config) {
+        document.assign_missing_ids();
+    }
+}
+
+/// ファイル末尾の改行を確保する
+pub struct TrailingNewline;
+
+impl NormalizeRule for TrailingNewline {
+    fn name(&self) -> &'static str {
+        "trailing_newline"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.trailing_newline
+    }
+
+    fn apply(&self, document: &mut TaskDocument, _config: &Config) {
+        document.trailing_newline = true;
+    }
+}
+
+/// タスク一覧の最上位のタスクを優先度順に並べ替える（デフォルトは無効）
+pub struct SortTasks;
+
+impl NormalizeRule for SortTasks {
+    fn name(&self) -> &'static str {
+        "sort_tasks"
+    }
+
+    fn is_enabled(&self, config: &Config) -> bool {
+        config.normalize.sort_tasks
+    }
+
+    fn apply(&self, document: &mut TaskDocument, _config: &Config) {
+        if let Some(section) = document.section_index(&document.headings.task) {
+            sort_section(document, section, SortKey::Priority, false);
+        }
+    }
+}
+
+/// 正規化ルールの一覧（適用順）
+pub fn rules() -> Vec<Box<dyn NormalizeRule>> {
+    vec![
+        Box::new(TaskHeading),
+        Box::new(CollapseBlankLines),
+        Box::new(DefaultPriority),
+        Box::new(UnarchiveIncomplete),
+        Box::new(SortTasks),
+        Box::new(AssignIds),
+        Box::new(TrailingNewline),
+    ]
+}
+
+/// 設定で有効なルールを順に適用し、内容を変更したルールの名前を返す
+pub fn normalize_document(document: &mut TaskDocument, config: &Config) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    for rule in rules() {
+        if !rule.is_enabled(config) {
+            continue;
+        }
+        let before = document.to_string();
+        rule.apply(document, config);
+        if document.to_string() != before {
+            changed.push(rule.name());
+        }
+    }
+    changed
+}
@@ -0,0 +1,187 @@
+use crate::document::{TaskDocument, TaskLocation};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+/// 読み取り専用コマンドの出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 人が読むための表示（デフォルト）
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+/// `list` の1タスク分の出力
+#[derive(Serialize, Debug, Default)]
+pub struct TaskRecord {
+    /// タスク番号（アーカイブファイルのタスクは番号で指定できないため `None`）
+    pub number: Option<usize>,
+    pub id: Option<String>,
+    /// `open` / `in-progress` / `waiting` / `done` / `cancelled`
+    pub state: String,
+    pub priority: Option<String>,
+    /// 優先度記号とIDコメントを除いた本文
+    pub text: String,
+    /// 見出しの `#` を除いたセクション名
+    pub section: Option<String>,
+    /// アーカイブファイルのタスクの場合はそのファイル（タスクファイルなら `None`）
+    pub file: Option<String>,
+    pub line: usize,
+    pub depth: usize,
+    /// 親タスクの番号（アーカイブファイルのタスクは `None`）
+    pub parent: Option<usize>,
+    pub due: Option<String>,
+    pub created: Option<String>,
+    pub completed: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl TaskRecord {
+    pub fn new(document: &TaskDocument, location: &TaskLocation) -> Self {
+        let task = document.task(location);
+        TaskRecord {
+            number: Some(location.number),
+            id: task.id().map(str::to_string),
+            state: task.state.name().to_string(),
+            priority: task.priority().map(str::to_string),
+            text: task.text().to_string(),
+            section: document.sections[location.section]
+                .title()
+                .map(str::to_string),
+            file: None,
+            line: location.line,
+            depth: location.depth,
+            parent: location.parent,
+            due: task.due().map(|due| due.to_string()),
+            created: task.created().map(|created| created.to_string()),
+            completed: task.completed().map(|completed| completed.to_string()),
+            tags: task.tags().iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    /// アーカイブファイル `file_path` のタスク。番号はタスクファイルの番号と重なるため出力しない
+    pub fn archived(file_path: &str, document: &TaskDocument, location: &TaskLocation) -> Self {
+        TaskRecord {
+            number: None,
+            parent: None,
+            file: Some(file_path.to_string()),
+            ..TaskRecord::new(document, location)
+        }
+    }
+}
+
+/// `tags` の1タグ分の出力
+#[derive(Serialize, Debug, Default)]
+pub struct TagRecord {
+    pub tag: String,
+    pub open: usize,
+    pub done: usize,
+}
+
+/// `history` の1操作分の出力
+#[derive(Serialize, Debug, Default)]
+pub struct HistoryRecord {
+    pub timestamp: String,
+    pub command: String,
+    pub undone: bool,
+}
+
+// 区切り文字や改行を含むセルをCSV/TSVとして安全な形にする
+fn escape_cell(cell: &str, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Tsv => cell.replace(['\t', '\n', '\r'], " "),
+        _ if cell.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        }
+        _ => cell.to_string(),
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<_>>().join(" "),
+        other => other.to_string(),
+    }
+}
+
+fn print_row(cells: &[String], format: OutputFormat) {
+    let separator = if format == OutputFormat::Tsv {
+        "\t"
+    } else {
+        ","
+    };
+    let row: Vec<String> = cells.iter().map(|c| escape_cell(c, format)).collect();
+    println!("{}", row.join(separator));
+}
+
+fn to_object<T: Serialize>(value: &T) -> serde_json::Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// レコードの一覧を出力する。JSONはオブジェクトの配列、CSV/TSVはヘッダー付きの表になる
+pub fn print_records<T: Serialize + Default>(format: OutputFormat, records: &[T]) {
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(records).unwrap_or_default()
+        );
+        return;
+    }
+
+    let headers: Vec<String> = to_object(&T::default()).keys().cloned().collect();
+    print_row(&headers, format);
+    for record in records {
+        let cells: Vec<String> = to_object(record).values().map(cell).collect();
+        print_row(&cells, format);
+    }
+}
+
+// 入れ子の値を `a.b.0.c` 形式のキーと値の組に展開する
+fn flatten(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&join(key), value, rows);
+            }
+        }
+        Value::Array(values) if values.iter().any(|v| v.is_object()) => {
+            for (i, value) in values.iter().enumerate() {
+                flatten(&join(&i.to_string()), value, rows);
+            }
+        }
+        other => rows.push((prefix.to_string(), cell(other))),
+    }
+}
+
+/// 1つの値を出力する。CSV/TSVでは `key,value` の表に展開する
+pub fn print_value<T: Serialize>(format: OutputFormat, value: &T) {
+    let value = serde_json::to_value(value).unwrap_or_default();
+    if format == OutputFormat::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).unwrap_or_default()
+        );
+        return;
+    }
+
+    let mut rows = Vec::new();
+    flatten("", &value, &mut rows);
+    print_row(&["key".to_string(), "value".to_string()], format);
+    for (key, value) in rows {
+        print_row(&[key, value], format);
+    }
+}
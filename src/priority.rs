@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// 優先度の1段階（例: `high` / `🔴`、`P0` / `[P0]`、`A` / `(A)`）
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriorityLevel {
+    /// `add --priority` や `default_priority` で指定する名前
+    pub name: String,
+    /// タスクの本文の先頭に書く記号
+    pub symbol: String,
+}
+
+impl PriorityLevel {
+    fn new(name: &str, symbol: &str) -> Self {
+        PriorityLevel {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+        }
+    }
+}
+
+/// 優先度の段階。`levels` の順が優先度の高い順になる
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PriorityScale {
+    pub levels: Vec<PriorityLevel>,
+}
+
+impl Default for PriorityScale {
+    fn default() -> Self {
+        PriorityScale {
+            levels: vec![
+                PriorityLevel::new("high", "🔴"),
+                PriorityLevel::new("medium", "🟡"),
+                PriorityLevel::new("low", "🟢"),
+            ],
+        }
+    }
+}
+
+/// 優先度の段階の設定の問題
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScaleError {
+    /// 段階が1つもない
+    Empty,
+    /// 名前または記号が空の段階がある
+    EmptyField,
+    /// 同じ名前または記号が複数の段階にある
+    Duplicate(String),
+}
+
+impl PriorityScale {
+    /// 名前の段階（0が最も高い）
+    pub fn level(&self, name: &str) -> Option<usize> {
+        self.levels.iter().position(|level| level.name == name)
+    }
+
+    /// 名前に対応する記号
+    pub fn symbol(&self, name: &str) -> Option<&str> {
+        self.level(name)
+            .map(|index| self.levels[index].symbol.as_str())
+    }
+
+    /// 本文の先頭にある優先度記号の段階。前方一致する記号が複数あれば最も長いもの
+    pub fn find(&self, content: &str) -> Option<usize> {
+        self.levels
+            .iter()
+            .enumerate()
+            .filter(|(_, level)| content.starts_with(&level.symbol))
+            .max_by_key(|(_, level)| level.symbol.len())
+            .map(|(index, _)| index)
+    }
+
+    /// 名前の一覧（`high, medium, low`）
+    pub fn names(&self) -> String {
+        self.levels
+            .iter()
+            .map(|level| level.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// 名前と記号が空でなく、重複していないことを確かめる
+    pub fn validate(&self) -> Result<(), ScaleError> {
+        if self.levels.is_empty() {
+            return Err(ScaleError::Empty);
+        }
+        let mut seen = Vec::new();
+        for level in &self.levels {
+            if level.name.is_empty() || level.symbol.is_empty() {
+                return Err(ScaleError::EmptyField);
+            }
+            for value in [&level.name, &level.symbol] {
+                if seen.contains(&value) {
+                    return Err(ScaleError::Duplicate(value.clone()));
+                }
+            }
+            seen.push(&level.name);
+            seen.push(&level.symbol);
+        }
+        Ok(())
+    }
+}
+
+static SCALE: OnceLock<PriorityScale> = OnceLock::new();
+
+/// 使用する優先度の段階を設定する（`main` で設定を読み込んだ後に1回だけ呼ぶ）
+pub fn set_scale(scale: PriorityScale) {
+    let _ = SCALE.set(scale);
+}
+
+/// 現在の優先度の段階（未設定なら high / medium / low）
+pub fn scale() -> &'static PriorityScale {
+    SCALE.get_or_init(PriorityScale::default)
+}
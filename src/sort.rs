@@ -0,0 +1,146 @@
+use crate::document::{Node, TaskDocument, TaskItem, TaskLocation};
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+/// タスクの並べ替えの基準
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// 優先度の高い順（`priority.levels` の順）
+    Priority,
+    /// 期日の早い順
+    Due,
+    /// 作成日（`➕`）の古い順。作成日のないタスクは後ろにファイル内の順番で並べる
+    Created,
+    /// 本文のアルファベット順（大文字・小文字を区別しない）
+    Alpha,
+}
+
+// 値がないタスクは `reverse` でも常に後ろにする
+fn compare_option<T: Ord>(a: Option<T>, b: Option<T>, reverse: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if reverse => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// 並べ替えた順序（`tasks` の添字）を返す。基準が同じタスクは元の順序を保つ
+pub fn sort_order(tasks: &[&TaskItem], key: SortKey, reverse: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by(|&i, &j| {
+        let (a, b) = (tasks[i], tasks[j]);
+        match key {
+            SortKey::Priority => compare_option(a.priority_level(), b.priority_level(), reverse),
+            SortKey::Due => compare_option(a.due(), b.due(), reverse),
+            SortKey::Created => compare_option(a.created(), b.created(), reverse)
+                .then_with(|| compare_option(Some(i), Some(j), reverse)),
+            SortKey::Alpha => compare_option(
+                Some(a.text().to_lowercase()),
+                Some(b.text().to_lowercase()),
+                reverse,
+            ),
+        }
+    });
+    order
+}
+
+/// 一覧表示の順序。各セクション内で最上位のタスクを並べ替え、サブタスクは親の直後に元の順序で続ける
+pub fn sorted_tasks(document: &TaskDocument, key: SortKey, reverse: bool) -> Vec<TaskLocation> {
+    reorder_blocks(document, |tasks| sort_order(tasks, key, reverse))
+}
+
+/// 各セクション内で最上位のタスクをファイル内の順番の逆に並べる。サブタスクは親の直後に元の順序で続ける
+pub fn reversed_tasks(document: &TaskDocument) -> Vec<TaskLocation> {
+    reorder_blocks(document, |tasks| (0..tasks.len()).rev().collect())
+}
+
+// セクションごとに最上位のタスクを `order` が返す順序（添字）に並べる
+fn reorder_blocks(
+    document: &TaskDocument,
+    order: impl Fn(&[&TaskItem]) -> Vec<usize>,
+) -> Vec<TaskLocation> {
+    // 最上位のタスクごとに、サブタスクを含めたまとまりにする
+    let mut blocks: Vec<Vec<TaskLocation>> = Vec::new();
+    for location in document.tasks() {
+        match blocks.last_mut() {
+            Some(block) if location.depth > 0 => block.push(location),
+            _ => blocks.push(vec![location]),
+        }
+    }
+
+    let mut sorted = Vec::new();
+    let mut start = 0;
+    while start < blocks.len() {
+        let section = blocks[start][0].section;
+        let end = start
+            + blocks[start..]
+                .iter()
+                .take_while(|block| block[0].section == section)
+                .count();
+        let tasks: Vec<&TaskItem> = blocks[start..end]
+            .iter()
+            .map(|block| document.task(&block[0]))
+            .collect();
+        for index in order(&tasks) {
+            sorted.extend(blocks[start + index].iter().cloned());
+        }
+        start = end;
+    }
+    sorted
+}
+
+/// セクション内の最上位のタスクを、サブタスクやメモと一緒に並べ替える。並べ替えたタスクの数を返す
+///
+/// タスクの間の空行は位置を変えずに残すため、空行で区切ったレイアウトは保たれる。
+pub fn sort_section(
+    document: &mut TaskDocument,
+    section: usize,
+    key: SortKey,
+    reverse: bool,
+) -> usize {
+    let starts: Vec<usize> = document
+        .tasks()
+        .into_iter()
+        .filter(|location| location.section == section && location.depth == 0)
+        .map(|location| location.node)
+        .collect();
+    let Some(&first) = starts.first() else {
+        return 0;
+    };
+
+    // 最上位のタスクから次の最上位のタスクの手前までを1つのまとまりとし、末尾の空行は区切りとして分ける
+    let mut nodes = std::mem::take(&mut document.sections[section].nodes);
+    let rest = nodes.split_off(first);
+    let mut blocks: Vec<Vec<Node>> = Vec::new();
+    let mut gaps: Vec<Vec<Node>> = Vec::new();
+    let mut rest = rest.into_iter();
+    for (i, &start) in starts.iter().enumerate() {
+        let len = starts.get(i + 1).map_or(usize::MAX, |&next| next - start);
+        let mut block: Vec<Node> = rest.by_ref().take(len).collect();
+        let content = block
+            .iter()
+            .rposition(|node| !node.is_blank())
+            .map_or(0, |i| i + 1);
+        gaps.push(block.split_off(content));
+        blocks.push(block);
+    }
+
+    let tasks: Vec<&TaskItem> = blocks
+        .iter()
+        .map(|block| match &block[0] {
+            Node::Task(task) => task,
+            Node::Text(_) => unreachable!("a block starts with a task"),
+        })
+        .collect();
+    let order = sort_order(&tasks, key, reverse);
+
+    let mut blocks: Vec<Option<Vec<Node>>> = blocks.into_iter().map(Some).collect();
+    for (index, gap) in order.into_iter().zip(gaps) {
+        nodes.extend(blocks[index].take().unwrap_or_default());
+        nodes.extend(gap);
+    }
+    document.sections[section].nodes = nodes;
+    starts.len()
+}
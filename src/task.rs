@@ -1,220 +1,1045 @@
-use crate::config::Config;
+use crate::config::{ArchiveTarget, Config, DuplicatePolicy, TrashMode};
+use crate::date::{self, parse_date};
+use crate::dedupe::{DuplicateGroup, find_duplicate, find_duplicates, merge_duplicates};
+use crate::document::{
+    CREATED_SYMBOL, DONE_SYMBOL, DUE_SYMBOL, Headings, Node, TaskDocument, TaskItem, TaskLocation,
+    TaskRef, TaskSelector, TaskState, dedent_block, malformed_task_lines,
+};
+use crate::error::MdTaskError;
+use crate::i18n::Message;
+use crate::normalize::normalize_document;
+use crate::priority;
+use crate::sort::{SortKey, sort_section};
+use crate::storage::write_atomic;
+use chrono::{Days, NaiveDate};
+use clap::ValueEnum;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::process::Command;
 
 fn is_verbose() -> bool {
     std::env::var("MD_TASK_VERBOSE").is_ok() || std::env::var("MD_TASK_DEBUG").is_ok()
 }
 
-pub fn normalize_task_file(file_path: &str, config: &Config) -> std::io::Result<()> {
+/// 設定で指定されたタスク一覧・アーカイブの見出し
+pub fn headings(config: &Config) -> Headings {
+    Headings::new(
+        config.sections.heading_level,
+        &config.sections.task_heading,
+        &config.sections.archive_heading,
+        &config.sections.trash_heading,
+    )
+}
+
+/// 設定の見出しでタスクファイルを読み込む
+pub fn load_document(file_path: &str, config: &Config) -> std::io::Result<TaskDocument> {
+    Ok(TaskDocument::load(file_path)?.with_headings(headings(config)))
+}
+
+/// アーカイブファイルとゴミ箱ファイルのタスクのID（新しいIDが重ならないようにする）
+pub fn reserved_ids(file_path: &str, config: &Config) -> std::io::Result<HashSet<String>> {
+    let mut documents: Vec<TaskDocument> = load_archive_documents(file_path, config)?
+        .into_iter()
+        .map(|(_, document)| document)
+        .collect();
+    if config.trash.mode == TrashMode::File {
+        documents.push(load_trash_document(file_path, config)?.1);
+    }
+    Ok(documents
+        .iter()
+        .flat_map(|document| {
+            document
+                .tasks()
+                .into_iter()
+                .chain(document.trashed_tasks())
+                .filter_map(|location| document.task(&location).id().map(str::to_string))
+        })
+        .collect())
+}
+
+/// タスクファイル（`file_path`）の内容を正規化した結果を返す（ファイルは変更しない）
+pub fn normalize_contents(
+    file_path: &str,
+    contents: &str,
+    config: &Config,
+) -> Result<String, MdTaskError> {
+    let mut document = TaskDocument::parse(contents)
+        .with_headings(headings(config))
+        .with_reserved_ids(reserved_ids(file_path, config)?);
+    normalize_document(&mut document, config);
+    Ok(document.to_string())
+}
+
+/// タスクファイルを正規化する。ファイルを書き換えた場合は `true`
+pub fn normalize_task_file(file_path: &str, config: &Config) -> Result<bool, MdTaskError> {
     let contents = match std::fs::read_to_string(file_path) {
         Ok(contents) => contents,
-        Err(_) => return Ok(()), // ファイルが存在しない場合は何もしない
+        Err(_) => return Ok(false), // ファイルが存在しない場合は何もしない
     };
 
-    let mut lines: Vec<String> = contents.lines().map(|s| s.to_string()).collect();
-    let mut modified = false;
+    // 変更があった場合のみファイルを更新
+    let mut document = TaskDocument::parse(&contents)
+        .with_headings(headings(config))
+        .with_reserved_ids(reserved_ids(file_path, config)?);
+    let rules = normalize_document(&mut document, config);
+    let new_contents = document.to_string();
+    if new_contents == contents {
+        return Ok(false);
+    }
+    write_atomic(file_path, &new_contents)?;
+    if is_verbose() {
+        println!("{}", Message::FileNormalized(&rules.join(", ")));
+    }
+    Ok(true)
+}
+
+/// `add` で本文と優先度以外に指定できる項目
+#[derive(Debug, Default)]
+pub struct TaskOptions {
+    pub due: Option<NaiveDate>,
+    /// `#` を除いたタグ名
+    pub tags: Vec<String>,
+    /// 指定した場合はこのタスクのサブタスクとして追加する
+    pub parent: Option<TaskRef>,
+}
 
-    // 1. 先頭に「## タスク一覧」セクションがない場合は追加
-    if lines.is_empty() || lines[0] != "## タスク一覧" {
-        lines.insert(0, "".to_string());
-        lines.insert(0, "## タスク一覧".to_string());
-        modified = true;
+/// `list` と `--filter` の絞り込み条件。指定された条件をすべて満たすタスクだけを対象にする
+#[derive(Debug, Default, Clone)]
+pub struct TaskFilter {
+    /// この日付より前が期日のタスク
+    pub due_before: Option<NaiveDate>,
+    /// 期日を過ぎた未完了タスク
+    pub overdue: bool,
+    /// 全てのタグを持つタスク
+    pub tags: Vec<String>,
+    /// この優先度名のタスク
+    pub priority: Option<String>,
+    /// この状態のタスク
+    pub state: Option<TaskState>,
+    /// 本文に全ての語を含むタスク（大文字・小文字を区別しない）
+    pub words: Vec<String>,
+}
+
+impl TaskFilter {
+    /// `--filter` の条件式を解釈する。空白区切りの条件を全て満たすタスクが対象になる
+    ///
+    /// `#tag`、`priority:high`、`due<2024-12-31`（相対指定も可）、`overdue`、状態名（`open`、`done` など）
+    /// のいずれでもない語は本文に含まれる語として扱う。
+    pub fn parse(expr: &str, today: NaiveDate) -> Result<Self, MdTaskError> {
+        let mut filter = TaskFilter::default();
+        for term in expr.split_whitespace() {
+            if let Some(tag) = term.strip_prefix('#') {
+                filter.tags.push(tag.to_string());
+            } else if let Some(name) = term.strip_prefix("priority:") {
+                if priority::scale().level(name).is_none() {
+                    return Err(MdTaskError::InvalidPriority(name.to_string()));
+                }
+                filter.priority = Some(name.to_string());
+            } else if let Some(due) = term.strip_prefix("due<") {
+                let due = parse_date(due, today)
+                    .map_err(|_| MdTaskError::InvalidDate(due.to_string()))?;
+                filter.due_before = Some(due);
+            } else {
+                match term {
+                    "overdue" => filter.overdue = true,
+                    _ => match TaskState::from_str(term, false) {
+                        Ok(state) => filter.state = Some(state),
+                        Err(_) => filter.words.push(term.to_lowercase()),
+                    },
+                }
+            }
+        }
+        Ok(filter)
     }
 
-    // 2. 連続する空行を1行にまとめる
-    let mut normalized_lines = Vec::new();
-    let mut prev_empty = false;
+    pub fn matches(&self, task: &TaskItem, today: NaiveDate) -> bool {
+        let due = task.due();
+        if let Some(before) = self.due_before
+            && due.is_none_or(|due| due >= before)
+        {
+            return false;
+        }
+        if self.overdue && (task.is_closed() || due.is_none_or(|due| due >= today)) {
+            return false;
+        }
+        if self.priority.is_some() && task.priority() != self.priority.as_deref() {
+            return false;
+        }
+        if self.state.is_some_and(|state| task.state != state) {
+            return false;
+        }
+        let text = task.text().to_lowercase();
+        self.words.iter().all(|word| text.contains(word))
+            && self.tags.iter().all(|tag| task.has_tag(tag))
+    }
+}
 
-    for line in lines {
-        let is_empty = line.trim().is_empty();
+/// `done` / `remove` / `archive` の対象。引数で指定したタスクと `--filter` に一致するタスク
+///
+/// 番号は全てコマンドの実行前のファイルに対して解決する。
+#[derive(Debug, Default, Clone)]
+pub struct TaskTargets {
+    pub selectors: Vec<TaskSelector>,
+    pub filter: Option<TaskFilter>,
+    /// `--filter` に指定した条件式（メッセージ用）
+    pub filter_expr: String,
+}
 
-        if is_empty && prev_empty {
-            // 連続する空行はスキップ
-            continue;
+impl TaskTargets {
+    /// 複数のタスクを対象にする可能性がある指定か
+    pub fn is_bulk(&self) -> bool {
+        self.filter.is_some()
+            || self.selectors.len() > 1
+            || self
+                .selectors
+                .iter()
+                .any(|selector| matches!(selector, TaskSelector::Range(..)))
+    }
+}
+
+impl From<TaskRef> for TaskTargets {
+    fn from(task_ref: TaskRef) -> Self {
+        TaskTargets {
+            selectors: vec![TaskSelector::Task(task_ref)],
+            ..Default::default()
         }
+    }
+}
 
-        // 3. 優先度記号がないタスクに medium 優先度を追加
-        if line.starts_with("- [ ]") || line.starts_with("- [x]") {
-            let task_content = &line[6..];
-            if !task_content.starts_with("🔴")
-                && !task_content.starts_with("🟡")
-                && !task_content.starts_with("🟢")
-            {
-                let new_line = format!("{} 🟡 {}", &line[..5], task_content);
-                normalized_lines.push(new_line);
-                modified = true;
-            } else {
-                normalized_lines.push(line);
+// 対象のタスクの位置をファイル内の順に重複なく返す。
+// 引数で指定したタスクが存在しないか `is_target` を満たさなければ `not_found` のエラーにする
+fn select_tasks(
+    document: &TaskDocument,
+    targets: &TaskTargets,
+    is_target: impl Fn(&TaskDocument, &TaskLocation) -> bool,
+    not_found: impl Fn(String) -> MdTaskError,
+) -> Result<Vec<TaskLocation>, MdTaskError> {
+    let tasks = document.tasks();
+    let mut selected = vec![false; tasks.len()];
+
+    for selector in &targets.selectors {
+        match selector {
+            TaskSelector::Task(task_ref) => match document.find_task(task_ref) {
+                Some(location) if is_target(document, &location) => {
+                    selected[location.number - 1] = true;
+                }
+                _ => return Err(not_found(task_ref.to_string())),
+            },
+            TaskSelector::Range(start, end) => {
+                // 範囲を展開する前に、最後のタスクより後ろの番号を含む範囲を拒否する
+                if *start == 0 || *end > tasks.len() {
+                    let missing = if *start == 0 {
+                        0
+                    } else {
+                        (*start).max(tasks.len() + 1)
+                    };
+                    return Err(not_found(missing.to_string()));
+                }
+                // 範囲内の対象にならないタスク（完了済みのタスクや親と一緒に移るサブタスクなど）は飛ばす
+                let mut found = false;
+                for location in &tasks[start - 1..*end] {
+                    if is_target(document, location) {
+                        selected[location.number - 1] = true;
+                        found = true;
+                    }
+                }
+                if !found {
+                    return Err(not_found(selector.to_string()));
+                }
             }
-        } else {
-            normalized_lines.push(line);
         }
+    }
 
-        prev_empty = is_empty;
+    if let Some(filter) = &targets.filter {
+        let today = date::today();
+        let matched: Vec<&TaskLocation> = tasks
+            .iter()
+            .filter(|location| {
+                is_target(document, location) && filter.matches(document.task(location), today)
+            })
+            .collect();
+        if matched.is_empty() {
+            return Err(MdTaskError::NoMatchingTasks(targets.filter_expr.clone()));
+        }
+        for location in matched {
+            selected[location.number - 1] = true;
+        }
     }
 
-    // 4. アーカイブ内の未完了タスクをタスク一覧に戻す（設定による）
-    if !config.task_management.allow_incomplete_in_archive {
-        let mut in_archive = false;
-        let mut tasks_to_move = Vec::new();
-        let mut final_lines = Vec::new();
+    Ok(tasks
+        .into_iter()
+        .filter(|location| selected[location.number - 1])
+        .collect())
+}
 
-        for line in normalized_lines {
-            if line == "## アーカイブ" {
-                in_archive = true;
-                final_lines.push(line);
-                continue;
-            }
+// 他の対象のサブタスクを除く（親と一緒に移動・削除されるため）
+fn outermost(document: &TaskDocument, locations: Vec<TaskLocation>) -> Vec<TaskLocation> {
+    let mut result: Vec<TaskLocation> = Vec::new();
+    for location in locations {
+        let inside = result.last().is_some_and(|last| {
+            last.section == location.section
+                && location.node < document.sections[last.section].block_end(last.node)
+        });
+        if !inside {
+            result.push(location);
+        }
+    }
+    result
+}
 
-            if in_archive && line.starts_with("- [ ]") {
-                // アーカイブ内の未完了タスクを移動対象に
-                tasks_to_move.push(line);
-                modified = true;
-            } else {
-                final_lines.push(line);
+/// タスクを追加し、割り当てたタスクIDを返す
+///
+/// 同じ本文の未完了タスクがある場合は `task_management.on_duplicate` に従って警告するか、
+/// `DuplicateTask` で追加しない（サブタスクとして追加する場合は確認しない）。
+pub fn add_task_to_file(
+    file_path: &str,
+    config: &Config,
+    task: &str,
+    priority: &str,
+    options: &TaskOptions,
+) -> Result<String, MdTaskError> {
+    // 優先度記号の設定
+    let priority_symbol = priority::scale()
+        .symbol(priority)
+        .ok_or_else(|| MdTaskError::InvalidPriority(priority.to_string()))?;
+
+    // ファイルが存在しない場合は「## タスク一覧」付きで新規作成される
+    let mut document = match std::fs::read_to_string(file_path) {
+        Ok(contents) => TaskDocument::parse(&contents),
+        Err(_) => TaskDocument::default(),
+    }
+    .with_headings(headings(config))
+    .with_reserved_ids(reserved_ids(file_path, config)?);
+
+    let id = document.generate_id();
+    let mut body = format!("{} {}", priority_symbol, task);
+    for tag in &options.tags {
+        body = format!("{} #{}", body, tag);
+    }
+
+    let mut new_task = TaskItem::new(TaskState::Open, &body);
+    if config.task_management.record_created {
+        new_task.set_date_field(CREATED_SYMBOL, Some(date::today()));
+    }
+    new_task.set_date_field(DUE_SYMBOL, options.due);
+    new_task.set_meta("id", &id);
+
+    if options.parent.is_none()
+        && let Some(duplicate) = find_duplicate(&document, &new_task)
+    {
+        match config.task_management.on_duplicate {
+            DuplicatePolicy::Allow => {}
+            DuplicatePolicy::Warn => {
+                eprintln!("{}", Message::DuplicateTaskWarning(duplicate.number));
+            }
+            DuplicatePolicy::Refuse => {
+                return Err(MdTaskError::DuplicateTask(duplicate.number));
             }
         }
+    }
+
+    match &options.parent {
+        Some(parent_ref) => {
+            let parent = document
+                .find_task(parent_ref)
+                .ok_or_else(|| MdTaskError::ParentNotFound(parent_ref.to_string()))?;
+            document.push_subtask(&parent, new_task);
+        }
+        None => {
+            let index = document.task_section_index();
+            document.sections[index].push_task(new_task);
+        }
+    }
+    document.save(file_path)?;
+    Ok(id)
+}
+
+/// タスクの本文を置き換える。優先度記号・状態・ID などのメタデータはそのまま残す
+pub fn edit_task_text(
+    file_path: &str,
+    config: &Config,
+    task_ref: &TaskRef,
+    text: &str,
+) -> Result<(), MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+    let location = document
+        .find_task(task_ref)
+        .ok_or_else(|| MdTaskError::TaskNotFound(task_ref.to_string()))?;
+    document.task_mut(&location).set_text(text);
+    document.save(file_path)?;
+    Ok(())
+}
+
+/// タスクの優先度記号を `priority` の記号に置き換える
+pub fn set_task_priority(
+    file_path: &str,
+    config: &Config,
+    task_ref: &TaskRef,
+    priority: &str,
+) -> Result<(), MdTaskError> {
+    let symbol = priority::scale()
+        .symbol(priority)
+        .ok_or_else(|| MdTaskError::InvalidPriority(priority.to_string()))?;
+
+    let mut document = load_document(file_path, config)?;
+    let location = document
+        .find_task(task_ref)
+        .ok_or_else(|| MdTaskError::TaskNotFound(task_ref.to_string()))?;
+    document.task_mut(&location).set_priority_symbol(symbol);
+    document.save(file_path)?;
+    Ok(())
+}
+
+/// タスクファイルをエディタでタスクの行から開く（`editor +行番号 ファイル`）。
+/// 編集後に、タスクとして解釈できなくなったチェックボックスの行番号を返す
+///
+/// `editor` は空白で区切って引数を指定できる（例: `code --wait`）。
+pub fn edit_in_editor(
+    file_path: &str,
+    config: &Config,
+    task_ref: &TaskRef,
+    editor: &str,
+) -> Result<Vec<usize>, MdTaskError> {
+    let document = load_document(file_path, config)?;
+    let location = document
+        .find_task(task_ref)
+        .ok_or_else(|| MdTaskError::TaskNotFound(task_ref.to_string()))?;
+
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(args)
+        .arg(format!("+{}", location.line))
+        .arg(file_path)
+        .status()?;
+    if !status.success() {
+        return Err(MdTaskError::EditorFailed(editor.to_string()));
+    }
+
+    let contents = std::fs::read_to_string(file_path)?;
+    Ok(malformed_task_lines(&contents))
+}
 
-        // 移動するタスクをタスク一覧セクションに挿入
-        if !tasks_to_move.is_empty() {
-            let mut archive_index = final_lines.len();
-            for (i, line) in final_lines.iter().enumerate() {
-                if line == "## アーカイブ" {
-                    archive_index = i;
-                    break;
+/// タグごとの未完了・完了済み（アーカイブ済みを含む）のタスク数
+///
+/// アーカイブファイルのタスクも数えるため、タスクファイルとアーカイブファイルの文書をまとめて渡す。
+pub fn count_tags<'a>(
+    documents: impl IntoIterator<Item = &'a TaskDocument>,
+) -> BTreeMap<String, (usize, usize)> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for document in documents {
+        for location in document.tasks() {
+            let task = document.task(&location);
+            for tag in task.tags() {
+                let entry = counts.entry(tag.to_string()).or_default();
+                if task.is_closed() {
+                    entry.1 += 1;
+                } else {
+                    entry.0 += 1;
                 }
             }
+        }
+    }
+    counts
+}
+
+/// 指定した未完了タスクを完了にし、完了にしたタスクの番号を返す。
+/// 引数で指定したタスクがないか完了済み・中止なら `TaskNotFound`
+///
+/// `cascade` が指定された場合はサブタスクもまとめて完了にする。
+/// `task_management.record_completed` が有効なら完了日（`✅ 2024-12-31`）を記録する。
+pub fn complete_tasks(
+    file_path: &str,
+    config: &Config,
+    targets: &TaskTargets,
+    cascade: bool,
+) -> Result<Vec<usize>, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+
+    let locations = select_tasks(
+        &document,
+        targets,
+        |document, location| !document.task(location).is_closed(),
+        MdTaskError::TaskNotFound,
+    )?;
 
-            // アーカイブセクションの前にタスクを挿入
-            for (i, task) in tasks_to_move.into_iter().enumerate() {
-                final_lines.insert(archive_index + i, task);
+    let completed = config.task_management.record_completed.then(date::today);
+    let complete = |task: &mut TaskItem| {
+        task.state = TaskState::Done;
+        if let Some(completed) = completed {
+            task.set_date_field(DONE_SYMBOL, Some(completed));
+        }
+    };
+    for location in &locations {
+        if cascade {
+            for descendant in document.descendants(location) {
+                let task = document.task_mut(&descendant);
+                // 中止したサブタスクは中止のままにする
+                if !task.is_closed() {
+                    complete(task);
+                }
             }
         }
+        complete(document.task_mut(location));
+    }
+    document.save(file_path)?;
+    Ok(locations.iter().map(|location| location.number).collect())
+}
+
+/// 指定した未完了タスクを `state`（進行中・保留・中止）にし、変更したタスクの番号を返す。
+/// 引数で指定したタスクがないか、完了済み・中止またはすでにその状態なら `TaskNotFound`
+pub fn set_task_state(
+    file_path: &str,
+    config: &Config,
+    targets: &TaskTargets,
+    state: TaskState,
+) -> Result<Vec<usize>, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
 
-        normalized_lines = final_lines;
+    let locations = select_tasks(
+        &document,
+        targets,
+        |document, location| {
+            let task = document.task(location);
+            !task.is_closed() && task.state != state
+        },
+        MdTaskError::TaskNotFound,
+    )?;
+
+    for location in &locations {
+        document.task_mut(location).state = state;
     }
+    document.save(file_path)?;
+    Ok(locations.iter().map(|location| location.number).collect())
+}
+
+/// 重複したタスクを統合し、（見つかった重複のまとまりの数, 取り除いたタスクの数）を返す
+///
+/// まとまりごとに `confirm` に渡し、`true` が返されたものだけを統合する。
+pub fn dedupe_task_file(
+    file_path: &str,
+    config: &Config,
+    mut confirm: impl FnMut(&TaskDocument, &DuplicateGroup) -> bool,
+) -> Result<(usize, usize), MdTaskError> {
+    let mut document = load_document(file_path, config)?;
 
-    // 5. ファイル末尾の改行を確保
-    if !normalized_lines.is_empty() && !normalized_lines.last().unwrap().is_empty() {
-        normalized_lines.push("".to_string());
-        modified = true;
+    let groups = find_duplicates(&document);
+    let accepted: Vec<DuplicateGroup> = groups
+        .iter()
+        .filter(|group| confirm(&document, group))
+        .cloned()
+        .collect();
+    if accepted.is_empty() {
+        return Ok((groups.len(), 0));
     }
 
-    // 変更があった場合のみファイルを更新
-    if modified {
-        let new_contents = normalized_lines.join("\n");
-        std::fs::write(file_path, new_contents)?;
-        if is_verbose() {
-            println!("File format normalized.");
+    let merged = merge_duplicates(&mut document, &accepted);
+    document.save(file_path)?;
+    Ok((groups.len(), merged))
+}
+
+/// 指定したタスクをサブタスクごと削除し、削除したタスクの番号を返す。
+/// 引数で指定したタスクがなければ `TaskNotFound`
+///
+/// 削除する前に `confirm` に対象を渡し、`false` が返されたら何もせずに `Aborted` にする。
+pub fn remove_tasks(
+    file_path: &str,
+    config: &Config,
+    targets: &TaskTargets,
+    confirm: impl FnOnce(&TaskDocument, &[TaskLocation]) -> bool,
+) -> Result<Vec<usize>, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+
+    let locations = select_tasks(&document, targets, |_, _| true, MdTaskError::TaskNotFound)?;
+    if !confirm(&document, &locations) {
+        return Err(MdTaskError::Aborted);
+    }
+
+    let outermost = outermost(&document, locations.clone());
+    if config.trash.mode == TrashMode::Delete {
+        document.remove_blocks(&outermost);
+    } else {
+        let today = date::today().format(date::DATE_FORMAT).to_string();
+        // 後ろから取り除くと前の位置は変わらない。サブタスクもゴミ箱では最上位のタスクにする
+        let mut blocks = Vec::new();
+        for location in outermost.iter().rev() {
+            let task = document.task_mut(location);
+            task.set_meta("removed", &today);
+            let indent = task.indent.clone();
+            let mut block = document.remove_block(location);
+            dedent_block(&mut block, &indent);
+            blocks.push(block);
+        }
+        blocks.reverse();
+        let blocks = blocks.concat();
+
+        if config.trash.mode == TrashMode::File {
+            let (trash_path, mut trash) = load_trash_document(file_path, config)?;
+            trash.push_trashed(blocks);
+            trash.save(&trash_path)?;
+        } else {
+            document.push_trashed(blocks);
         }
     }
+    document.save(file_path)?;
+    Ok(locations.iter().map(|location| location.number).collect())
+}
+
+/// `trash.mode = "file"` の場合のゴミ箱ファイル。`trash.file` が空ならタスクファイルの `.md` を `.trash.md` にしたもの
+pub fn trash_file_path(file_path: &str, config: &Config) -> String {
+    if !config.trash.file.is_empty() {
+        return config.trash.file.clone();
+    }
+    match file_path.strip_suffix(".md") {
+        Some(stem) => format!("{}.trash.md", stem),
+        None => format!("{}.trash", file_path),
+    }
+}
+
+/// ゴミ箱のあるファイルのパスとドキュメント。`trash.mode = "file"` ならゴミ箱ファイル（なければ空）、
+/// それ以外はタスクファイル
+pub fn load_trash_document(
+    file_path: &str,
+    config: &Config,
+) -> std::io::Result<(String, TaskDocument)> {
+    if config.trash.mode != TrashMode::File {
+        return Ok((file_path.to_string(), load_document(file_path, config)?));
+    }
+    let trash_path = trash_file_path(file_path, config);
+    let document = load_optional_document(&trash_path, config)?;
+    Ok((trash_path, document))
+}
+
+/// ゴミ箱のタスク（ゴミ箱での番号またはID）をサブタスクごとタスク一覧の優先度の位置に戻す
+pub fn restore_task(
+    file_path: &str,
+    config: &Config,
+    task_ref: &TaskRef,
+) -> Result<(), MdTaskError> {
+    let (trash_path, mut trash) = load_trash_document(file_path, config)?;
+    let location = trash
+        .find_trashed(task_ref)
+        .ok_or_else(|| MdTaskError::TaskNotFound(task_ref.to_string()))?;
+    trash.task_mut(&location).remove_meta("removed");
+    let block = trash.remove_block(&location);
 
+    if trash_path == file_path {
+        trash.insert_by_priority(block);
+    } else {
+        let mut document = load_document(file_path, config)?;
+        document.insert_by_priority(block);
+        document.save(file_path)?;
+    }
+    trash.save(&trash_path)?;
     Ok(())
 }
 
-pub fn add_task_to_file(file_path: &str, task: &str, priority: &str) -> std::io::Result<()> {
-    // 優先度記号の設定
-    let priority_symbol = match priority {
-        "high" => "🔴",
-        "medium" => "🟡",
-        "low" => "🟢",
-        _ => "🟡", // デフォルト
+/// ゴミ箱のタスクを完全に削除し、削除したタスクの数を返す
+///
+/// `older_than` を指定した場合は、削除してからその日数より長く経ったタスク（削除日のないタスクを含む）だけを削除する。
+pub fn empty_trash(
+    file_path: &str,
+    config: &Config,
+    older_than: Option<u64>,
+) -> Result<usize, MdTaskError> {
+    let (trash_path, mut trash) = load_trash_document(file_path, config)?;
+    let cutoff = match older_than {
+        Some(days) => match date::today().checked_sub_days(Days::new(days)) {
+            Some(cutoff) => Some(cutoff),
+            // 日付で表せないほど長い期間を指定した場合は何も削除しない
+            None => return Ok(0),
+        },
+        None => None,
     };
+    let locations: Vec<TaskLocation> = trash
+        .trashed_tasks()
+        .into_iter()
+        .filter(|location| location.depth == 0)
+        .filter(|location| {
+            let removed = trash
+                .task(location)
+                .meta("removed")
+                .and_then(|removed| NaiveDate::parse_from_str(removed, date::DATE_FORMAT).ok());
+            match (cutoff, removed) {
+                (Some(cutoff), Some(removed)) => removed < cutoff,
+                _ => true,
+            }
+        })
+        .collect();
+
+    if !locations.is_empty() {
+        trash.remove_blocks(&locations);
+        trash.save(&trash_path)?;
+    }
+    Ok(locations.len())
+}
 
-    let new_task_line = format!("- [ ] {} {}", priority_symbol, task);
+// サブタスクは親と一緒にアーカイブされるため、トップレベルの完了済み・中止タスクのみが対象
+fn is_archivable(document: &TaskDocument, location: &TaskLocation) -> bool {
+    location.depth == 0
+        && document.task(location).is_closed()
+        && !document.is_archive(location.section)
+}
 
-    if let Ok(contents) = std::fs::read_to_string(file_path) {
-        // ファイルが存在する場合：適切な位置に挿入
-        let mut lines: Vec<String> = contents.lines().map(|s| s.to_string()).collect();
-        let mut archive_section_index = None;
+/// 指定した完了済み・中止タスクをサブタスクごとアーカイブし、アーカイブしたタスクの番号を返す。
+/// 引数で指定したタスクがないかアーカイブできなければ `CompletedTaskNotFound`
+///
+/// アーカイブ先は `archive.target` に従う（タスクファイルのセクション、1つのファイル、月ごとのファイル）。
+pub fn archive_tasks(
+    file_path: &str,
+    config: &Config,
+    targets: &TaskTargets,
+) -> Result<Vec<usize>, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
 
-        // アーカイブセクションの位置を探す
-        for (i, line) in lines.iter().enumerate() {
-            if line == "## アーカイブ" {
-                archive_section_index = Some(i);
-                break;
-            }
-        }
+    let locations = select_tasks(
+        &document,
+        targets,
+        is_archivable,
+        MdTaskError::CompletedTaskNotFound,
+    )?;
 
-        if let Some(index) = archive_section_index {
-            // アーカイブセクションがある場合：その前に挿入
-            lines.insert(index, new_task_line);
-        } else {
-            // アーカイブセクションがない場合：最後に追加
-            lines.push(new_task_line);
-        }
+    archive_blocks(file_path, config, &mut document, &locations)?;
+    document.save(file_path)?;
+    Ok(locations.iter().map(|location| location.number).collect())
+}
 
-        // ファイルを上書き保存
-        let new_contents = lines.join("\n");
-        std::fs::write(file_path, new_contents)?;
+// タスクファイルと同じ場所のパス
+fn sibling_path(file_path: &str, name: &str) -> String {
+    Path::new(file_path)
+        .with_file_name(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+// `archive.target = "monthly"` の場合のディレクトリ
+fn archive_dir(file_path: &str, config: &Config) -> String {
+    if config.archive.dir.is_empty() {
+        sibling_path(file_path, "archive")
     } else {
-        // ファイルが存在しない場合：新規作成（## タスク一覧付き）
-        let initial_content = format!("## タスク一覧\n\n{}", new_task_line);
-        std::fs::write(file_path, initial_content)?;
+        config.archive.dir.clone()
+    }
+}
+
+/// `archive.target` が `"file"` / `"monthly"` の場合のアーカイブファイル。`"monthly"` では
+/// `completed` の月のファイル（`archive/2024-12.md`）
+pub fn archive_file_path(file_path: &str, config: &Config, completed: NaiveDate) -> String {
+    match config.archive.target {
+        ArchiveTarget::Monthly => Path::new(&archive_dir(file_path, config))
+            .join(format!("{}.md", completed.format("%Y-%m")))
+            .to_string_lossy()
+            .into_owned(),
+        _ if config.archive.file.is_empty() => sibling_path(file_path, "archive.md"),
+        _ => config.archive.file.clone(),
+    }
+}
+
+// アーカイブファイルやゴミ箱ファイルを読み込む（なければ空のドキュメント）
+fn load_optional_document(path: &str, config: &Config) -> std::io::Result<TaskDocument> {
+    let document = match std::fs::read_to_string(path) {
+        Ok(contents) => TaskDocument::parse(&contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => TaskDocument {
+            trailing_newline: true,
+            ..Default::default()
+        },
+        Err(e) => return Err(e),
+    };
+    Ok(document.with_headings(headings(config)))
+}
+
+/// タスクファイルの外にある既存のアーカイブファイル（新しい順）。`archive.target = "section"` なら空
+pub fn archive_paths(file_path: &str, config: &Config) -> std::io::Result<Vec<String>> {
+    let paths = match config.archive.target {
+        ArchiveTarget::Section => Vec::new(),
+        ArchiveTarget::File => vec![archive_file_path(file_path, config, date::today())],
+        ArchiveTarget::Monthly => {
+            let mut paths: Vec<String> = match std::fs::read_dir(archive_dir(file_path, config)) {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e),
+            };
+            paths.sort();
+            paths.reverse();
+            paths
+        }
+    };
+    Ok(paths
+        .into_iter()
+        .filter(|path| Path::new(path).exists())
+        .collect())
+}
+
+/// タスクファイルの外にあるアーカイブファイルのパスとドキュメント（新しい順）
+pub fn load_archive_documents(
+    file_path: &str,
+    config: &Config,
+) -> std::io::Result<Vec<(String, TaskDocument)>> {
+    let mut documents = Vec::new();
+    for path in archive_paths(file_path, config)? {
+        let document = load_optional_document(&path, config)?;
+        documents.push((path, document));
+    }
+    Ok(documents)
+}
+
+/// コマンドがタスクファイルと一緒に書き換えることのあるファイル（操作履歴に記録する）
+pub fn related_files(file_path: &str, config: &Config) -> std::io::Result<Vec<String>> {
+    let mut paths = archive_paths(file_path, config)?;
+    if config.trash.mode == TrashMode::File {
+        paths.push(trash_file_path(file_path, config));
+    }
+    Ok(paths)
+}
+
+// 最上位のタスクをサブタスクごと取り除き、`archive.target` に従ってアーカイブセクションかアーカイブファイルに移す
+//
+// 月ごとのファイルは完了日（なければ今日）で選ぶ。ファイルは `document` より先に保存する。
+fn archive_blocks(
+    file_path: &str,
+    config: &Config,
+    document: &mut TaskDocument,
+    locations: &[TaskLocation],
+) -> std::io::Result<()> {
+    if config.archive.target == ArchiveTarget::Section {
+        let blocks = document.remove_blocks(locations);
+        document.push_archived(blocks);
+        return Ok(());
+    }
+
+    let today = date::today();
+    // 後ろから取り除くと前の位置は変わらない。ファイルごとにファイル内の順序でまとめる
+    let mut files: Vec<(String, Vec<Node>)> = Vec::new();
+    for location in locations.iter().rev() {
+        let completed = document.task(location).completed().unwrap_or(today);
+        let path = archive_file_path(file_path, config, completed);
+        let block = document.remove_block(location);
+        match files.iter_mut().find(|(other, _)| *other == path) {
+            Some((_, nodes)) => {
+                nodes.splice(0..0, block);
+            }
+            None => files.push((path, block)),
+        }
     }
 
+    for (path, nodes) in files {
+        let mut archive = load_optional_document(&path, config)?;
+        archive.push_archived(nodes);
+        if let Some(dir) = Path::new(&path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        archive.save(&path)?;
+    }
     Ok(())
 }
 
-pub fn archive_all_completed_tasks(file_path: &str) -> std::io::Result<()> {
-    // ファイルを読み込む
-    let contents = std::fs::read_to_string(file_path)?;
+// アーカイブ内のサブタスクは親と一緒に戻すため、単独では再開できない
+fn is_reopenable(document: &TaskDocument, location: &TaskLocation) -> bool {
+    document.task(location).is_closed()
+        && (location.depth == 0 || !document.is_archive(location.section))
+}
 
-    let mut lines: Vec<String> = Vec::new();
-    let mut completed_tasks: Vec<String> = Vec::new();
-    let mut archive_section_exists = false;
-    let mut archive_section_start = 0;
-
-    // 1. 完了済みタスクを収集し、それらをメインセクションから除外
-    for line in contents.lines() {
-        if line == "## アーカイブ" {
-            archive_section_exists = true;
-            archive_section_start = lines.len();
-            lines.push(line.to_string());
-        } else if line.starts_with("- [x]") {
-            // 完了済みタスクの場合
-            if archive_section_exists {
-                // すでにアーカイブセクション内の場合はそのまま保持
-                lines.push(line.to_string());
-            } else {
-                // タスク一覧セクション内の場合はアーカイブ対象として収集
-                completed_tasks.push(line.to_string());
+// 未完了に戻し、完了日を取り除いて再開した日付を記録する
+fn reopen(task: &mut TaskItem, today: &str) {
+    task.state = TaskState::Open;
+    task.set_date_field(DONE_SYMBOL, None);
+    task.set_meta("reopened", today);
+}
+
+/// 指定した完了済み・中止タスクを未完了に戻し、戻したタスクの番号を返す。
+/// 引数で指定したタスクがないか再開できなければ `TaskNotFound`
+///
+/// 完了日を取り除き、再開した日付をメタデータ（`reopened:2024-12-31`）に記録する。
+/// アーカイブ内のタスクはサブタスクごとタスク一覧の優先度の位置に戻す。
+/// タスクファイルにないIDはアーカイブファイル（`archive.target`）の最上位のタスクからも探す。
+/// 返す番号は移動した後のタスクファイルでの番号。
+pub fn reopen_tasks(
+    file_path: &str,
+    config: &Config,
+    targets: &TaskTargets,
+) -> Result<Vec<usize>, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+
+    // タスクファイルにないIDのうち、アーカイブファイルで見つかったものは別に扱う
+    let mut archives = load_archive_documents(file_path, config)?;
+    let mut external: Vec<(usize, TaskLocation)> = Vec::new();
+    let mut local = TaskTargets {
+        selectors: Vec::new(),
+        ..targets.clone()
+    };
+    for selector in &targets.selectors {
+        if let TaskSelector::Task(task_ref @ TaskRef::Id(_)) = selector
+            && document.find_task(task_ref).is_none()
+            && let Some(found) = archives
+                .iter()
+                .enumerate()
+                .find_map(|(index, (_, archive))| {
+                    archive
+                        .find_task(task_ref)
+                        .filter(|location| {
+                            location.depth == 0 && archive.task(location).is_closed()
+                        })
+                        .map(|location| (index, location))
+                })
+        {
+            external.push(found);
+            continue;
+        }
+        local.selectors.push(selector.clone());
+    }
+
+    let locations = if local.selectors.is_empty() && local.filter.is_none() {
+        Vec::new()
+    } else {
+        select_tasks(&document, &local, is_reopenable, MdTaskError::TaskNotFound)?
+    };
+
+    let today = date::today().format(date::DATE_FORMAT).to_string();
+    for location in &locations {
+        reopen(document.task_mut(location), &today);
+    }
+
+    // 戻したタスクの位置（セクション, 行）。挿入で後ろの行がずれた分はその都度補正する
+    let mut reopened: Vec<(usize, usize)> = locations
+        .iter()
+        .filter(|location| !document.is_archive(location.section))
+        .map(|location| (location.section, location.node))
+        .collect();
+    let mut insert = |document: &mut TaskDocument, block: Vec<Node>| {
+        let len = block.len();
+        let (section, node) = document.insert_by_priority(block);
+        for position in reopened.iter_mut() {
+            if position.0 == section && position.1 >= node {
+                position.1 += len;
             }
-        } else {
-            lines.push(line.to_string());
         }
+        reopened.push((section, node));
+    };
+
+    let archived: Vec<TaskLocation> = locations
+        .iter()
+        .filter(|location| document.is_archive(location.section))
+        .copied()
+        .collect();
+    // 後ろから取り除くと前の位置は変わらない
+    let mut blocks: Vec<_> = archived
+        .iter()
+        .rev()
+        .map(|location| document.remove_block(location))
+        .collect();
+    blocks.reverse();
+    for block in blocks {
+        insert(&mut document, block);
+    }
+
+    // アーカイブファイルのタスクは後ろから取り除き、タスクファイルに戻す
+    external.sort_by_key(|&(index, location)| std::cmp::Reverse((index, location.node)));
+    for (index, location) in &external {
+        let archive = &mut archives[*index].1;
+        reopen(archive.task_mut(location), &today);
+        let block = archive.remove_block(location);
+        insert(&mut document, block);
+    }
+    let mut changed: Vec<usize> = external.iter().map(|(index, _)| *index).collect();
+    changed.dedup();
+    for index in changed {
+        let (path, archive) = &archives[index];
+        archive.save(path)?;
     }
 
+    document.save(file_path)?;
+    // 移動した後の番号を返す
+    let tasks = document.tasks();
+    let mut numbers: Vec<usize> = reopened
+        .iter()
+        .filter_map(|&(section, node)| {
+            tasks
+                .iter()
+                .find(|location| location.section == section && location.node == node)
+        })
+        .map(|location| location.number)
+        .collect();
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
+/// 完了済み・中止のタスクをまとめてアーカイブする
+pub fn archive_all_completed_tasks(file_path: &str, config: &Config) -> Result<(), MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+
+    // 1. アーカイブ対象の完了済み・中止タスクを収集
+    let locations: Vec<TaskLocation> = document
+        .tasks()
+        .into_iter()
+        .filter(|location| is_archivable(&document, location))
+        .collect();
+
     // 2. 完了済みタスクがなければ何もしない
-    if completed_tasks.is_empty() {
+    if locations.is_empty() {
         if is_verbose() {
-            println!("No completed tasks to archive.");
+            println!("{}", Message::NoCompletedTasks);
         }
         return Ok(());
     }
 
-    // 3. アーカイブセクションがない場合は作成
-    if !archive_section_exists {
-        lines.push("".to_string()); // 空行
-        lines.push("## アーカイブ".to_string());
-        archive_section_start = lines.len() - 1;
+    // 3. サブタスクごとアーカイブセクション（なければ作成）かアーカイブファイルに追加して保存
+    archive_blocks(file_path, config, &mut document, &locations)?;
+    document.save(file_path)?;
+
+    if is_verbose() {
+        println!("{}", Message::ArchivedCount(locations.len()));
     }
 
-    // 4. 完了済みタスクをアーカイブセクションに追加
-    let insert_position = archive_section_start + 1;
-    for (i, task) in completed_tasks.iter().enumerate() {
-        if i == 0 {
-            lines.insert(insert_position, "".to_string()); // アーカイブセクションの後に空行
+    Ok(())
+}
+
+/// 見出しのタイトルが `from_title` のセクションを設定の見出しに置き換える（見出しレベルは問わない）。
+/// 置き換えた見出しの数を返す
+pub fn migrate_headings(
+    file_path: &str,
+    config: &Config,
+    from_task_title: &str,
+    from_archive_title: &str,
+) -> Result<usize, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+    let headings = document.headings.clone();
+
+    let mut renamed = 0;
+    for section in &mut document.sections {
+        let target = match section.title() {
+            Some(title) if title == from_task_title => &headings.task,
+            Some(title) if title == from_archive_title => &headings.archive,
+            _ => continue,
+        };
+        if section.heading.as_ref() != Some(target) {
+            section.heading = Some(target.clone());
+            renamed += 1;
         }
-        lines.insert(insert_position + i + 1, task.clone());
     }
 
-    // 5. ファイルを更新
-    let new_contents = lines.join("\n");
-    std::fs::write(file_path, new_contents)?;
-
-    if is_verbose() {
-        println!("Archived {} completed tasks.", completed_tasks.len());
+    if renamed > 0 {
+        document.save(file_path)?;
     }
+    Ok(renamed)
+}
 
-    Ok(())
+/// タスク一覧セクションの最上位のタスクを並べ替えて保存する。並べ替えたタスクの数を返す
+pub fn sort_task_file(
+    file_path: &str,
+    config: &Config,
+    key: SortKey,
+    reverse: bool,
+) -> Result<usize, MdTaskError> {
+    let mut document = load_document(file_path, config)?;
+    let Some(section) = document.section_index(&document.headings.task) else {
+        return Ok(0);
+    };
+
+    let sorted = sort_section(&mut document, section, key, reverse);
+    document.save(file_path)?;
+    Ok(sorted)
 }
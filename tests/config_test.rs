@@ -6,6 +6,12 @@ use tempfile::tempdir;
 // md-taskのconfig.rsをテストするためにクレートを再エクスポート
 #[path = "../src/config.rs"]
 mod config;
+#[allow(dead_code)]
+#[path = "../src/i18n.rs"]
+mod i18n;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
 
 #[test]
 fn test_load_config_from_local_file() {
@@ -34,7 +40,7 @@ task_file = "test-tasks.md"
     fs::write("md-task.toml", config_content).unwrap();
 
     // 設定を読み込む
-    let config = config::load_config();
+    let config = config::load_config().unwrap();
 
     // 設定値を検証
     assert_eq!(config.task_management.default_priority, "high");
@@ -43,6 +49,14 @@ task_file = "test-tasks.md"
     assert!(config.display.show_completed_by_default);
     assert_eq!(config.file_paths.task_file, "test-tasks.md");
 
+    // [sections] がない設定ファイルでは見出しはデフォルトのまま
+    assert_eq!(config.sections.task_heading, "タスク一覧");
+    assert_eq!(config.sections.archive_heading, "アーカイブ");
+    assert_eq!(config.sections.heading_level, 2);
+
+    // display.locale がない場合は環境変数から判定する
+    assert_eq!(config.display.locale, "auto");
+
     // テスト後に元のディレクトリに戻す
     env::set_current_dir(original_dir).unwrap();
 }
@@ -63,3 +77,182 @@ fn test_get_config_dir() {
         env::remove_var("MD_TASK_DEV");
     }
 }
+
+#[test]
+fn test_load_layered_config() {
+    let temp_dir = tempdir().unwrap();
+    let global = temp_dir.path().join("config.toml");
+    let local = temp_dir.path().join("md-task.toml");
+    let missing = temp_dir.path().join("missing.toml");
+
+    // グローバル設定とローカル設定にそれぞれ一部の項目だけを書く
+    fs::write(
+        &global,
+        r#"
+[task_management]
+default_priority = "high"
+
+[file_paths]
+task_file = "global-tasks.md"
+
+[trash]
+mode = "file"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &local,
+        r#"
+[file_paths]
+task_file = "local-tasks.md"
+
+[archive]
+target = "monthly"
+
+[trash]
+file = "local.trash.md"
+"#,
+    )
+    .unwrap();
+
+    let (config, sources) =
+        config::load_layered_config(&[global.clone(), missing, local.clone()]).unwrap();
+
+    // 項目単位で重ねられ、書かれていない項目はデフォルトのまま
+    assert_eq!(config.task_management.default_priority, "high");
+    assert_eq!(config.file_paths.task_file, "local-tasks.md");
+    assert!(config.task_management.auto_format);
+    assert_eq!(config.sections.task_heading, "タスク一覧");
+    assert_eq!(config.trash.mode, config::TrashMode::File);
+    assert_eq!(config.trash.file, "local.trash.md");
+    assert_eq!(config.archive.target, config::ArchiveTarget::Monthly);
+    assert_eq!(config.archive.dir, "");
+
+    // 各値を設定したファイルが記録される
+    assert_eq!(
+        sources["task_management.default_priority"],
+        global.display().to_string()
+    );
+    assert_eq!(sources["file_paths.task_file"], local.display().to_string());
+    assert!(!sources.contains_key("task_management.auto_format"));
+    // 同じセクションでもキーごとに別のファイルから重ねられる
+    assert_eq!(sources["trash.mode"], global.display().to_string());
+    assert_eq!(sources["trash.file"], local.display().to_string());
+    assert!(!sources.contains_key("archive.dir"));
+}
+
+#[test]
+fn test_config_parse_error_position() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("md-task.toml");
+
+    // 3行目の値が文字列として閉じられていない
+    fs::write(
+        &path,
+        "[task_management]\nauto_format = true\ndefault_priority = high\n",
+    )
+    .unwrap();
+
+    let errors = config::validate_config_file(&path);
+    assert_eq!(errors.len(), 1);
+    match &errors[0] {
+        config::ConfigError::Parse {
+            line,
+            column,
+            source_line,
+            ..
+        } => {
+            assert_eq!(*line, 3);
+            assert_eq!(*column, 20);
+            assert_eq!(source_line, "default_priority = high");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    // 読み込み時は警告を表示して読み飛ばす（デフォルト値のまま）
+    let (config, _) = config::load_layered_config(&[path]).unwrap();
+    assert_eq!(config.task_management.default_priority, "medium");
+}
+
+#[test]
+fn test_validate_config_file() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("md-task.toml");
+
+    fs::write(
+        &path,
+        r#"
+[task_management]
+default_priority = "urgent"
+auto_fromat = false
+
+[colors]
+high = "red"
+"#,
+    )
+    .unwrap();
+
+    let errors = config::validate_config_file(&path);
+    let unknown: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| match error {
+            config::ConfigError::UnknownKey { key, .. } => Some(key.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(unknown, ["colors", "task_management.auto_fromat"]);
+
+    // default_priority は重ねた後の設定で検査する
+    let (config, sources) = config::load_layered_config(std::slice::from_ref(&path)).unwrap();
+    assert!(matches!(
+        config::check_default_priority(&config, &sources),
+        Some(config::ConfigError::InvalidPriority { value, .. }) if value == "urgent"
+    ));
+
+    // 存在しないファイルは問題なし
+    assert!(config::validate_config_file(&temp_dir.path().join("missing.toml")).is_empty());
+}
+
+#[test]
+fn test_priority_scale_across_layers() {
+    let temp_dir = tempdir().unwrap();
+    let global = temp_dir.path().join("config.toml");
+    let local = temp_dir.path().join("md-task.toml");
+
+    // グローバル設定で段階を定義し、ローカル設定でその段階のデフォルトを選ぶ
+    fs::write(
+        &global,
+        r#"
+[priority]
+levels = [
+    { name = "P0", symbol = "[P0]" },
+    { name = "P1", symbol = "[P1]" },
+    { name = "P2", symbol = "[P2]" },
+]
+"#,
+    )
+    .unwrap();
+    fs::write(&local, "[task_management]\ndefault_priority = \"P2\"\n").unwrap();
+
+    assert!(config::validate_config_file(&global).is_empty());
+    assert!(config::validate_config_file(&local).is_empty());
+    let (config, sources) = config::load_layered_config(&[global.clone(), local]).unwrap();
+    assert_eq!(config.priority.names(), "P0, P1, P2");
+    assert_eq!(config.priority.symbol("P2"), Some("[P2]"));
+    assert!(config::check_default_priority(&config, &sources).is_none());
+
+    // 重複した記号は段階の問題として報告する
+    fs::write(
+        &global,
+        r#"
+[priority]
+levels = [{ name = "A", symbol = "(A)" }, { name = "B", symbol = "(A)" }]
+"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        config::validate_config_file(&global).as_slice(),
+        [config::ConfigError::InvalidPriorityScale { error: priority::ScaleError::Duplicate(value), .. }]
+            if value == "(A)"
+    ));
+}
@@ -0,0 +1,56 @@
+use chrono::NaiveDate;
+
+// md-taskのdate.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/date.rs"]
+mod date;
+
+use date::{DueStatus, due_status, parse_date, parse_days};
+
+fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_parse_date() {
+    // 2026-10-17 は土曜日
+    let today = ymd(2026, 10, 17);
+
+    assert_eq!(parse_date("2024-12-31", today), Ok(ymd(2024, 12, 31)));
+    assert_eq!(parse_date("today", today), Ok(today));
+    assert_eq!(parse_date("tomorrow", today), Ok(ymd(2026, 10, 18)));
+    assert_eq!(parse_date("明日", today), Ok(ymd(2026, 10, 18)));
+    assert_eq!(parse_date("明後日", today), Ok(ymd(2026, 10, 19)));
+    assert_eq!(parse_date("3日後", today), Ok(ymd(2026, 10, 20)));
+    assert_eq!(parse_date("+3d", today), Ok(ymd(2026, 10, 20)));
+    assert_eq!(parse_date("+2w", today), Ok(ymd(2026, 10, 31)));
+    assert!(parse_date("+9999999999999999999w", today).is_err());
+    assert_eq!(parse_date("next-mon", today), Ok(ymd(2026, 10, 19)));
+    assert_eq!(parse_date("来週月曜", today), Ok(ymd(2026, 10, 19)));
+
+    // 同じ曜日を指定した場合は翌週になる
+    assert_eq!(parse_date("next-sat", today), Ok(ymd(2026, 10, 24)));
+
+    assert!(parse_date("someday", today).is_err());
+    assert!(parse_date("2024-13-01", today).is_err());
+}
+
+#[test]
+fn test_due_status() {
+    let today = ymd(2026, 10, 17);
+    assert_eq!(due_status(ymd(2026, 10, 15), today), DueStatus::Overdue(2));
+    assert_eq!(due_status(today, today), DueStatus::Today);
+    assert_eq!(due_status(ymd(2026, 10, 20), today), DueStatus::Upcoming(3));
+}
+
+#[test]
+fn test_parse_days() {
+    assert_eq!(parse_days("30d"), Ok(30));
+    assert_eq!(parse_days("2w"), Ok(14));
+    assert_eq!(parse_days("7"), Ok(7));
+    assert_eq!(parse_days("3日"), Ok(3));
+    assert_eq!(parse_days("2週間"), Ok(14));
+    assert!(parse_days("d").is_err());
+    assert!(parse_days("-1d").is_err());
+    assert!(parse_days("9999999999999999999w").is_err());
+}
@@ -0,0 +1,91 @@
+// md-taskのdedupe.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/date.rs"]
+mod date;
+#[path = "../src/dedupe.rs"]
+mod dedupe;
+#[allow(dead_code)]
+#[path = "../src/document.rs"]
+mod document;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+#[allow(dead_code)]
+#[path = "../src/storage.rs"]
+mod storage;
+
+use dedupe::{duplicate_key, find_duplicate, find_duplicates, merge_duplicates};
+use document::{TaskDocument, TaskItem};
+
+#[test]
+fn test_duplicate_key() {
+    let key = |line: &str| duplicate_key(&TaskItem::parse(line).unwrap());
+
+    // 優先度・ID・空白・全角半角・大文字小文字の違いは無視する
+    assert_eq!(
+        key("- [ ] 🔴 Check  the progress <!-- id:ab12 -->"),
+        key("- [ ] 🟡 ｃｈｅｃｋ the　progress")
+    );
+    assert_eq!(
+        key("- [ ] 🟡 重要な会議の準備"),
+        key("- [ ] 🔴 重要な 会議の準備")
+    );
+    assert_ne!(key("- [ ] 🟡 会議の準備"), key("- [ ] 🟡 会議の準備 #work"));
+
+    // 作成日が違っても同じタスク
+    assert_eq!(
+        key("- [ ] 🟡 会議の準備 ➕ 2026-10-01"),
+        key("- [ ] 🟡 会議の準備 ➕ 2026-10-17")
+    );
+}
+
+#[test]
+fn test_find_and_merge_duplicates() {
+    let contents = r#"## タスク一覧
+
+- [ ] 🟡 重要な会議の準備 <!-- id:aa11 -->
+  - [ ] 🟡 資料
+- [ ] 🔴 重要な会議の準備 <!-- id:bb22 -->
+- [ ] 🟢 メールチェック
+- [ ] 🟡 check the progress of the project
+- [x] 🟡 メールチェック
+- [ ] 🟡 check  the progress of the project
+
+## アーカイブ
+
+- [ ] 🟡 メールチェック
+"#;
+    let mut document = TaskDocument::parse(contents);
+
+    // 完了済みとアーカイブ内のタスクは対象にしない
+    let groups = find_duplicates(&document);
+    assert_eq!(groups.len(), 2);
+
+    // 優先度が最も高いタスクを残す
+    assert_eq!(groups[0].keep.number, 3);
+    assert_eq!(groups[0].merge.len(), 1);
+    assert!(groups[0].exact);
+    assert_eq!(groups[1].keep.number, 5);
+    assert!(!groups[1].exact);
+
+    let new_task = TaskItem::parse("- [ ] 🟢 メール チェック").unwrap();
+    assert_eq!(find_duplicate(&document, &new_task).unwrap().number, 4);
+
+    // 取り除いたタスクのサブタスクは残すタスクの下に移る
+    assert_eq!(merge_duplicates(&mut document, &groups), 2);
+    assert_eq!(
+        document.to_string(),
+        r#"## タスク一覧
+
+- [ ] 🔴 重要な会議の準備 <!-- id:bb22 -->
+  - [ ] 🟡 資料
+- [ ] 🟢 メールチェック
+- [ ] 🟡 check the progress of the project
+- [x] 🟡 メールチェック
+
+## アーカイブ
+
+- [ ] 🟡 メールチェック
+"#
+    );
+}
@@ -0,0 +1,45 @@
+// md-taskのdiff.rsをテストするためにクレートを再エクスポート
+#[path = "../src/diff.rs"]
+mod diff;
+
+use diff::unified_diff;
+
+#[test]
+fn test_unified_diff() {
+    assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a/x", "b/x"), "");
+
+    let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+    let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n";
+    let expected = "\
+--- a/tasks.md
++++ b/tasks.md
+@@ -1,6 +1,6 @@
+ 1
+ 2
+-3
++three
+ 4
+ 5
+ 6
+@@ -10,3 +10,4 @@
+ 10
+ 11
+ 12
++13
+";
+    assert_eq!(unified_diff(old, new, "a/tasks.md", "b/tasks.md"), expected);
+}
+
+#[test]
+fn test_unified_diff_missing_newline() {
+    let expected = "\
+--- a
++++ b
+@@ -1,2 +1,2 @@
+ x
+-y
+\\ No newline at end of file
++y
+";
+    assert_eq!(unified_diff("x\ny", "x\ny\n", "a", "b"), expected);
+}
@@ -0,0 +1,254 @@
+// md-taskのdate.rsとdocument.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/date.rs"]
+mod date;
+#[allow(dead_code)]
+#[path = "../src/document.rs"]
+mod document;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+#[path = "../src/storage.rs"]
+mod storage;
+
+use chrono::NaiveDate;
+use document::{
+    Node, TaskDocument, TaskItem, TaskRef, TaskSelector, TaskState, dedent_block,
+    malformed_task_lines,
+};
+use std::collections::HashSet;
+
+#[test]
+fn test_parse_round_trip() {
+    // 見出し・タスク・メモ・連続した空行が混在し、末尾に改行のない内容
+    let contents = r#"メモ: 見出しより前の行
+
+## タスク一覧
+
+- [ ] 🔴 未完了タスク
+- [x] 完了タスク
+  インデントされたメモ
+- [ ]優先度なし（スペースなし）
+
+
+#タグのような行
+### 小見出し
+## アーカイブ
+- [x] 🟡 アーカイブ済み"#;
+
+    let document = TaskDocument::parse(contents);
+    assert_eq!(document.to_string(), contents);
+
+    let with_newline = format!("{}\n", contents);
+    assert_eq!(TaskDocument::parse(&with_newline).to_string(), with_newline);
+    assert_eq!(TaskDocument::parse("").to_string(), "");
+}
+
+#[test]
+fn test_parse_crlf() {
+    // CRLFのファイルも見出しとタスクを認識し、書き出すときもCRLFのまま
+    let contents = "## タスク一覧\r\n\r\n- [ ] 🟡 b\r\n## アーカイブ\r\n- [x] 🟡 a\r\n";
+    let mut document = TaskDocument::parse(contents);
+    assert_eq!(document.to_string(), contents);
+
+    let tasks = document.tasks();
+    assert_eq!(tasks.len(), 2);
+    assert!(!document.is_archive(tasks[0].section));
+    assert!(document.is_archive(tasks[1].section));
+
+    // メタデータは行末の改行の前に書き込まれる
+    document.task_mut(&tasks[0]).set_meta("id", "f478");
+    assert_eq!(
+        document.to_string(),
+        "## タスク一覧\r\n\r\n- [ ] 🟡 b <!-- id:f478 -->\r\n## アーカイブ\r\n- [x] 🟡 a\r\n"
+    );
+}
+
+#[test]
+fn test_parse_structure() {
+    let contents = "## タスク一覧\n\n- [ ] 🔴 A\nメモ\n- [x] 🟢 B\n\n## アーカイブ\n\n- [x] 🟡 C\n";
+    let document = TaskDocument::parse(contents);
+
+    // セクションは見出しごとに分かれる
+    assert_eq!(document.sections.len(), 2);
+    assert!(document.is_archive(1));
+    assert!(matches!(&document.sections[0].nodes[2], Node::Text(text) if text == "メモ"));
+
+    // タスク番号はファイル全体の通し番号で、行番号も保持する
+    let tasks = document.tasks();
+    let numbered: Vec<(usize, usize, &str)> = tasks
+        .iter()
+        .map(|location| {
+            let task = document.task(location);
+            (location.number, location.line, task.content.as_str())
+        })
+        .collect();
+    assert_eq!(
+        numbered,
+        vec![(1, 3, "🔴 A"), (2, 5, "🟢 B"), (3, 9, "🟡 C")]
+    );
+    assert_eq!(document.task(&tasks[1]).state, TaskState::Done);
+    assert_eq!(document.task(&tasks[0]).priority_symbol(), Some("🔴"));
+}
+
+#[test]
+fn test_task_states() {
+    let states: Vec<TaskState> = ["- [ ] a", "- [/] a", "- [?] a", "- [x] a", "- [-] a"]
+        .iter()
+        .map(|line| TaskItem::parse(line).unwrap().state)
+        .collect();
+    assert_eq!(
+        states,
+        [
+            TaskState::Open,
+            TaskState::InProgress,
+            TaskState::Waiting,
+            TaskState::Done,
+            TaskState::Cancelled,
+        ]
+    );
+
+    // 状態を表さない文字のチェックボックスはタスクではない
+    assert!(TaskItem::parse("- [>] a").is_none());
+    assert!(TaskItem::parse("- [/]a").is_none());
+
+    let task = TaskItem::parse("  - [/] 🔴 進行中").unwrap();
+    assert_eq!(task.to_line(), "  - [/] 🔴 進行中");
+    assert!(TaskState::Cancelled.is_closed());
+    assert!(!TaskState::Waiting.is_closed());
+}
+
+#[test]
+fn test_edit_task_text_and_priority() {
+    let mut task = TaskItem::parse("- [ ] 会議 <!-- id:7f3a -->").unwrap();
+    task.set_priority_symbol("🔴");
+    assert_eq!(task.to_line(), "- [ ] 🔴 会議 <!-- id:7f3a -->");
+    task.set_text("会議の準備");
+    task.set_priority_symbol("🟢");
+    assert_eq!(task.to_line(), "- [ ] 🟢 会議の準備 <!-- id:7f3a -->");
+
+    // 作成日・完了日は期日と同じ形式の日付で、本文の末尾に入る
+    let mut task = TaskItem::parse("- [x] 🟡 会議 ➕ 2026-10-01 <!-- id:7f3a -->").unwrap();
+    assert_eq!(task.created(), NaiveDate::from_ymd_opt(2026, 10, 1));
+    assert_eq!(task.completed(), None);
+    task.set_date_field(document::DONE_SYMBOL, NaiveDate::from_ymd_opt(2026, 10, 17));
+    assert_eq!(
+        task.to_line(),
+        "- [x] 🟡 会議 ➕ 2026-10-01 ✅ 2026-10-17 <!-- id:7f3a -->"
+    );
+    assert_eq!(task.completed(), NaiveDate::from_ymd_opt(2026, 10, 17));
+
+    let contents = "- [ ] a\n- [>] b\n  -[ ] c\n- [x] d\n- 箇条書き\n";
+    assert_eq!(malformed_task_lines(contents), [2, 3]);
+}
+
+#[test]
+fn test_task_metadata_and_refs() {
+    let mut task = TaskItem::parse("- [ ] 🔴 会議の準備 <!-- id:7f3a -->").unwrap();
+    assert_eq!(task.id(), Some("7f3a"));
+    assert_eq!(task.body(), "🔴 会議の準備");
+
+    // 見出しのような `##` や単独の `#` はタグではない
+    let tagged = TaskItem::parse("- [ ] 🟡 #work 資料 #日本語 # ## a#b #x-y/z").unwrap();
+    assert_eq!(tagged.tags(), vec!["work", "日本語", "x-y/z"]);
+
+    // 既存のキーは置き換え、新しいキーは追加する
+    task.set_meta("id", "b2c4");
+    task.set_meta("note", "x");
+    assert_eq!(task.content, "🔴 会議の準備 <!-- id:b2c4 note:x -->");
+
+    // 数字のみはタスク番号、それ以外はタスクID
+    assert_eq!("3".parse::<TaskRef>(), Ok(TaskRef::Number(3)));
+    assert_eq!("7F3A".parse::<TaskRef>(), Ok(TaskRef::Id("7f3a".into())));
+    assert_eq!("id:1234".parse::<TaskRef>(), Ok(TaskRef::Id("1234".into())));
+    assert_eq!("^7f3a".parse::<TaskRef>(), Ok(TaskRef::Id("7f3a".into())));
+    assert!("7f 3a".parse::<TaskRef>().is_err());
+
+    // 一括操作では番号の範囲も指定できる
+    assert_eq!("5-7".parse::<TaskSelector>(), Ok(TaskSelector::Range(5, 7)));
+    assert_eq!("7-5".parse::<TaskSelector>(), Ok(TaskSelector::Range(5, 7)));
+    assert_eq!(
+        "7f3a".parse::<TaskSelector>(),
+        Ok(TaskSelector::Task(TaskRef::Id("7f3a".into())))
+    );
+    assert!("5-x".parse::<TaskSelector>().is_err());
+
+    // 生成されるIDは数字のみにならない
+    let id = TaskDocument::default().generate_id();
+    assert_eq!(id.len(), 4);
+    assert!(id.parse::<usize>().is_err());
+
+    // 他のファイルのIDは使わない（空いているIDが1つだけなら必ずそれになる）
+    let reserved: HashSet<String> = (0..=0xffff)
+        .map(|n| format!("{:04x}", n))
+        .filter(|id| id != "beef")
+        .collect();
+    let document = TaskDocument::parse("- [ ] a\n").with_reserved_ids(reserved);
+    assert_eq!(document.generate_id(), "beef");
+    let mut document = document;
+    document.assign_missing_ids();
+    assert_eq!(document.to_string(), "- [ ] a <!-- id:beef -->\n");
+
+    // 割り当てるIDは本文から決まるので、同じ内容なら何度実行しても同じになる
+    let contents = "- [ ] a\n- [ ] a\n- [ ] b\n";
+    let mut first = TaskDocument::parse(contents);
+    let mut second = TaskDocument::parse(contents);
+    assert_eq!(first.assign_missing_ids(), 3);
+    second.assign_missing_ids();
+    assert_eq!(first.to_string(), second.to_string());
+    let ids: HashSet<String> = first
+        .tasks()
+        .iter()
+        .filter_map(|location| first.task(location).id().map(str::to_string))
+        .collect();
+    assert_eq!(ids.len(), 3);
+}
+
+#[test]
+fn test_trash_section() {
+    let contents = r#"## タスク一覧
+
+- [ ] 🔴 タスク1
+
+## ゴミ箱
+
+- [ ] 🟡 削除したタスク <!-- id:aa11 removed:2026-10-01 -->
+  - [ ] 🟢 サブタスク
+"#;
+    let document = TaskDocument::parse(contents);
+
+    // ゴミ箱のタスクは通常の番号に含めず、ゴミ箱の中で番号を振る
+    assert_eq!(document.tasks().len(), 1);
+    let trashed = document.trashed_tasks();
+    assert_eq!(trashed.len(), 2);
+    assert_eq!(trashed[0].number, 1);
+    assert_eq!(trashed[1].parent, Some(1));
+    assert_eq!(document.find_trashed(&TaskRef::Number(1)), Some(trashed[0]));
+    assert_eq!(
+        document.find_trashed(&TaskRef::Id("aa11".into())),
+        Some(trashed[0])
+    );
+    // サブタスクは単独では戻せない
+    assert_eq!(document.find_trashed(&TaskRef::Number(2)), None);
+
+    let mut task = document.task(&trashed[0]).clone();
+    assert_eq!(task.meta("removed"), Some("2026-10-01"));
+    task.remove_meta("removed");
+    assert_eq!(task.content, "🟡 削除したタスク <!-- id:aa11 -->");
+    task.remove_meta("id");
+    assert_eq!(task.content, "🟡 削除したタスク");
+
+    // サブタスクを最上位のタスクとして移すときはインデントを浅くする
+    let mut block = vec![
+        Node::Task(TaskItem::parse("  - [ ] 🟢 サブタスク").unwrap()),
+        Node::Text("    メモ".to_string()),
+    ];
+    dedent_block(&mut block, "  ");
+    assert_eq!(
+        block,
+        [
+            Node::Task(TaskItem::parse("- [ ] 🟢 サブタスク").unwrap()),
+            Node::Text("  メモ".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,43 @@
+// md-taskのi18n.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/i18n.rs"]
+mod i18n;
+
+use i18n::{Locale, Message};
+
+#[test]
+fn test_locale_from_setting() {
+    assert_eq!(Locale::from_setting("ja"), Locale::Ja);
+    assert_eq!(Locale::from_setting("ja_JP.UTF-8"), Locale::Ja);
+    assert_eq!(Locale::from_setting("en"), Locale::En);
+    assert_eq!(Locale::from_setting("EN_us"), Locale::En);
+
+    // auto は環境変数から判定する
+    assert_eq!(Locale::from_setting("auto"), Locale::from_env());
+}
+
+#[test]
+fn test_message_text() {
+    let summary = Message::ListSummary {
+        open: 2,
+        done: 1,
+        archived: 3,
+    };
+    assert_eq!(
+        summary.text(Locale::En),
+        "Total: 2 open, 1 done, 3 archived"
+    );
+    assert_eq!(
+        summary.text(Locale::Ja),
+        "合計: 未完了 2件, 完了済み 1件, アーカイブ済み 3件"
+    );
+
+    assert_eq!(
+        Message::TaskNotFound("7f3a").text(Locale::En),
+        "ERROR: Task 7f3a not found."
+    );
+    assert_eq!(
+        Message::Overdue(2).text(Locale::Ja),
+        "⚠️ 期限切れ (2日超過)"
+    );
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use tempfile::tempdir;
+
+// md-taskのjournal.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/config.rs"]
+mod config;
+#[allow(dead_code)]
+#[path = "../src/error.rs"]
+mod error;
+#[allow(dead_code)]
+#[path = "../src/i18n.rs"]
+mod i18n;
+#[allow(dead_code)]
+#[path = "../src/journal.rs"]
+mod journal;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+#[path = "../src/storage.rs"]
+mod storage;
+
+use error::MdTaskError;
+use journal::{FileChange, Journal};
+
+#[test]
+fn test_undo_redo() {
+    let temp_dir = tempdir().unwrap();
+    let journal_path = temp_dir.path().join("journal.json");
+    let task_file = temp_dir.path().join("tasks.md");
+    let task_file_path = task_file.to_str().unwrap();
+
+    let first = "## タスク一覧\n\n- [ ] 🟡 A\n".to_string();
+    let second = "## タスク一覧\n\n- [ ] 🟡 A\n- [ ] 🟡 B\n".to_string();
+
+    // ファイルの新規作成とタスクの追加を記録
+    let mut journal = Journal::default();
+    fs::write(task_file_path, &first).unwrap();
+    journal.record(
+        task_file_path,
+        "add A",
+        None,
+        Some(first.clone()),
+        Vec::new(),
+    );
+    fs::write(task_file_path, &second).unwrap();
+    journal.record(
+        task_file_path,
+        "add B",
+        Some(first.clone()),
+        Some(second.clone()),
+        Vec::new(),
+    );
+    journal.save(&journal_path).unwrap();
+
+    // 新しい操作から順に取り消す
+    let mut journal = Journal::load(&journal_path).unwrap();
+    assert_eq!(journal.undo(task_file_path).unwrap().command, "add B");
+    assert_eq!(fs::read_to_string(task_file_path).unwrap(), first);
+    assert_eq!(journal.undo(task_file_path).unwrap().command, "add A");
+    assert!(!task_file.exists());
+    assert!(matches!(
+        journal.undo(task_file_path),
+        Err(MdTaskError::NothingToUndo)
+    ));
+
+    // 取り消した操作を古い順にやり直す
+    assert_eq!(journal.redo(task_file_path).unwrap().command, "add A");
+    assert_eq!(fs::read_to_string(task_file_path).unwrap(), first);
+
+    // 新しい操作を記録すると、取り消し済みの操作はやり直せなくなる
+    let third = "## タスク一覧\n\n- [x] 🟡 A\n".to_string();
+    fs::write(task_file_path, &third).unwrap();
+    journal.record(
+        task_file_path,
+        "done 1",
+        Some(first),
+        Some(third),
+        Vec::new(),
+    );
+    assert!(matches!(
+        journal.redo(task_file_path),
+        Err(MdTaskError::NothingToRedo)
+    ));
+    let commands: Vec<&str> = journal
+        .entries_for(task_file_path)
+        .iter()
+        .map(|entry| entry.command.as_str())
+        .collect();
+    assert_eq!(commands, ["add A", "done 1"]);
+}
+
+#[test]
+fn test_undo_refuses_to_overwrite_changes() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("tasks.md");
+    let task_file_path = task_file.to_str().unwrap();
+
+    let mut journal = Journal::default();
+    fs::write(task_file_path, "after\n").unwrap();
+    journal.record(
+        task_file_path,
+        "rm 3",
+        Some("before\n".to_string()),
+        Some("after\n".to_string()),
+        Vec::new(),
+    );
+
+    // 操作の後に手で編集された場合は書き戻さない
+    fs::write(task_file_path, "edited\n").unwrap();
+    let error = journal.undo(task_file_path).unwrap_err();
+    assert!(matches!(error, MdTaskError::FileChanged(_)));
+    assert_eq!(error.exit_code(), 5);
+    assert_eq!(fs::read_to_string(task_file_path).unwrap(), "edited\n");
+}
+
+#[test]
+fn test_undo_related_files() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("tasks.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let archive_file = temp_dir.path().join("archive.md");
+    let archive_file_path = archive_file.to_str().unwrap();
+
+    // タスクファイルからアーカイブファイルへタスクを移した操作
+    let mut journal = Journal::default();
+    fs::write(task_file_path, "## タスク一覧\n").unwrap();
+    fs::write(archive_file_path, "## アーカイブ\n\n- [x] 🟡 A\n").unwrap();
+    journal.record(
+        task_file_path,
+        "archive 1",
+        Some("## タスク一覧\n\n- [x] 🟡 A\n".to_string()),
+        Some("## タスク一覧\n".to_string()),
+        vec![FileChange::new(
+            archive_file_path,
+            None,
+            Some("## アーカイブ\n\n- [x] 🟡 A\n".to_string()),
+        )],
+    );
+
+    // 取り消すと両方のファイルが戻る（作られたファイルは削除される）
+    journal.undo(task_file_path).unwrap();
+    assert!(fs::read_to_string(task_file_path).unwrap().contains("A"));
+    assert!(!archive_file.exists());
+
+    // どれか1つのファイルが変更されていれば、どのファイルも書き換えない
+    fs::write(archive_file_path, "edited\n").unwrap();
+    let error = journal.redo(task_file_path).unwrap_err();
+    assert!(matches!(error, MdTaskError::FileChanged(file) if file.ends_with("archive.md")));
+    assert!(fs::read_to_string(task_file_path).unwrap().contains("A"));
+
+    fs::remove_file(archive_file_path).unwrap();
+    journal.redo(task_file_path).unwrap();
+    assert_eq!(
+        fs::read_to_string(task_file_path).unwrap(),
+        "## タスク一覧\n"
+    );
+    assert!(archive_file.exists());
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+use tempfile::tempdir;
+
+// md-taskのlock.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/config.rs"]
+mod config;
+#[allow(dead_code)]
+#[path = "../src/error.rs"]
+mod error;
+#[allow(dead_code)]
+#[path = "../src/i18n.rs"]
+mod i18n;
+#[path = "../src/lock.rs"]
+mod lock;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+
+use error::MdTaskError;
+use lock::FileLock;
+
+#[test]
+fn test_file_lock() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("tasks.md");
+    let task_file_path = task_file.to_str().unwrap();
+
+    assert_eq!(
+        lock::lock_path(task_file_path),
+        temp_dir.path().join(".tasks.md.lock")
+    );
+
+    // ロック中は待った後にロックしているプロセスのPIDを報告する
+    let first = FileLock::acquire(task_file_path, Duration::ZERO).unwrap();
+    let error = FileLock::acquire(task_file_path, Duration::from_millis(100)).unwrap_err();
+    match &error {
+        MdTaskError::FileLocked { pid, .. } => assert_eq!(*pid, Some(std::process::id())),
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(error.exit_code(), 6);
+
+    // 解放後は取得できる
+    drop(first);
+    assert!(FileLock::acquire(task_file_path, Duration::ZERO).is_ok());
+}
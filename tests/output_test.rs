@@ -0,0 +1,71 @@
+// md-taskのoutput.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/date.rs"]
+mod date;
+#[allow(dead_code)]
+#[path = "../src/document.rs"]
+mod document;
+#[allow(dead_code)]
+#[path = "../src/output.rs"]
+mod output;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+#[path = "../src/storage.rs"]
+mod storage;
+
+use document::TaskDocument;
+use output::TaskRecord;
+
+#[test]
+fn test_task_record() {
+    let contents = r#"## タスク一覧
+
+- [ ] 🔴 親タスク #work 📅 2026-10-20 <!-- id:7f3a -->
+  - [x] サブタスク
+
+## アーカイブ
+
+- [x] 🟢 古いタスク ➕ 2026-10-01 ✅ 2026-10-03
+"#;
+    let document = TaskDocument::parse(contents);
+    let records: Vec<TaskRecord> = document
+        .tasks()
+        .iter()
+        .map(|location| TaskRecord::new(&document, location))
+        .collect();
+
+    let parent = &records[0];
+    assert_eq!(parent.number, Some(1));
+    assert_eq!(parent.id.as_deref(), Some("7f3a"));
+    assert_eq!(parent.state, "open");
+    assert_eq!(parent.priority.as_deref(), Some("high"));
+    assert_eq!(parent.text, "親タスク #work 📅 2026-10-20");
+    assert_eq!(parent.section.as_deref(), Some("タスク一覧"));
+    assert_eq!(parent.line, 3);
+    assert_eq!(parent.due.as_deref(), Some("2026-10-20"));
+    assert_eq!(parent.tags, vec!["work"]);
+    assert_eq!(parent.created, None);
+
+    let subtask = &records[1];
+    assert_eq!(subtask.state, "done");
+    assert_eq!(subtask.priority, None);
+    assert_eq!((subtask.depth, subtask.parent), (1, Some(1)));
+
+    let archived = &records[2];
+    assert_eq!(archived.section.as_deref(), Some("アーカイブ"));
+    assert_eq!(archived.line, 8);
+    assert_eq!(archived.created.as_deref(), Some("2026-10-01"));
+    assert_eq!(archived.completed.as_deref(), Some("2026-10-03"));
+
+    // JSONのキーは構造体のフィールド順
+    let json = serde_json::to_string(&records[2]).unwrap();
+    assert!(json.starts_with(r#"{"number":3,"id":null,"state":"done","priority":"low""#));
+
+    // アーカイブファイルのタスクは番号と親の番号を出力しない
+    let archived = TaskRecord::archived("archive.md", &document, &document.tasks()[1]);
+    assert_eq!(archived.number, None);
+    assert_eq!(archived.parent, None);
+    assert_eq!(archived.file.as_deref(), Some("archive.md"));
+    assert_eq!(archived.depth, 1);
+}
@@ -0,0 +1,40 @@
+// md-taskのpriority.rsをテストするためにクレートを再エクスポート
+#[allow(dead_code)]
+#[path = "../src/date.rs"]
+mod date;
+#[allow(dead_code)]
+#[path = "../src/document.rs"]
+mod document;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+#[allow(dead_code)]
+#[path = "../src/storage.rs"]
+mod storage;
+
+use document::TaskItem;
+use priority::{PriorityLevel, PriorityScale};
+
+#[test]
+fn test_custom_priority_scale() {
+    let level = |name: &str, symbol: &str| PriorityLevel {
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+    };
+    // `(A)` と `(AA)` のように前方一致する記号は長い方を優先する
+    let scale = PriorityScale {
+        levels: vec![level("A", "(A)"), level("B", "(B)"), level("AA", "(AA)")],
+    };
+    assert!(scale.validate().is_ok());
+    assert_eq!(scale.find("(AA) 本文"), Some(2));
+    assert_eq!(scale.find("(A) 本文"), Some(0));
+    assert_eq!(scale.find("🔴 本文"), None);
+
+    // タスクの優先度は設定した段階に従う
+    priority::set_scale(scale);
+    let task = TaskItem::parse("- [ ] (B) レビュー #work").unwrap();
+    assert_eq!(task.priority(), Some("B"));
+    assert_eq!(task.priority_level(), Some(1));
+    assert_eq!(task.text(), "レビュー #work");
+    assert_eq!(TaskItem::parse("- [ ] 🔴 本文").unwrap().priority(), None);
+}
@@ -0,0 +1,24 @@
+use std::fs;
+use tempfile::tempdir;
+
+// md-taskのstorage.rsをテストするためにクレートを再エクスポート
+#[path = "../src/storage.rs"]
+mod storage;
+
+#[test]
+fn test_write_atomic() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("tasks.md");
+
+    // 新規作成と置き換え
+    storage::write_atomic(&path, "first\n").unwrap();
+    storage::write_atomic(&path, "second\n").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+
+    // 一時ファイルは残らない
+    let names: Vec<String> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, ["tasks.md"]);
+}
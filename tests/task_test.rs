@@ -1,9 +1,37 @@
+use chrono::NaiveDate;
+use document::{TaskDocument, TaskRef, TaskState};
+use error::MdTaskError;
 use std::fs;
+use task::{TaskFilter, TaskOptions};
 use tempfile::tempdir;
 
-// md-taskのconfig.rsとtask.rsをテストするためにクレートを再エクスポート
+// md-taskのconfig.rs、date.rs、document.rs、error.rs、i18n.rs、sort.rs、task.rsをテストするためにクレートを再エクスポート
 #[path = "../src/config.rs"]
 mod config;
+#[allow(dead_code)]
+#[path = "../src/date.rs"]
+mod date;
+#[allow(dead_code)]
+#[path = "../src/dedupe.rs"]
+mod dedupe;
+#[allow(dead_code)]
+#[path = "../src/document.rs"]
+mod document;
+#[allow(dead_code)]
+#[path = "../src/error.rs"]
+mod error;
+#[allow(dead_code)]
+#[path = "../src/i18n.rs"]
+mod i18n;
+#[path = "../src/normalize.rs"]
+mod normalize;
+#[allow(dead_code)]
+#[path = "../src/priority.rs"]
+mod priority;
+#[path = "../src/sort.rs"]
+mod sort;
+#[path = "../src/storage.rs"]
+mod storage;
 #[path = "../src/task.rs"]
 mod task;
 
@@ -16,9 +44,17 @@ fn test_add_task_to_file() {
     // テスト用のファイルパス
     let task_file = temp_path.join("test-tasks.md");
     let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
 
     // タスクを追加
-    task::add_task_to_file(task_file_path, "テストタスク", "high").unwrap();
+    task::add_task_to_file(
+        task_file_path,
+        &config,
+        "テストタスク",
+        "high",
+        &TaskOptions::default(),
+    )
+    .unwrap();
 
     // ファイルの内容を検証
     let content = fs::read_to_string(task_file_path).unwrap();
@@ -26,7 +62,14 @@ fn test_add_task_to_file() {
     assert!(content.contains("- [ ] 🔴 テストタスク"));
 
     // さらにタスクを追加
-    task::add_task_to_file(task_file_path, "普通の優先度タスク", "medium").unwrap();
+    task::add_task_to_file(
+        task_file_path,
+        &config,
+        "普通の優先度タスク",
+        "medium",
+        &TaskOptions::default(),
+    )
+    .unwrap();
 
     // 再度ファイルの内容を検証
     let content = fs::read_to_string(task_file_path).unwrap();
@@ -62,7 +105,7 @@ fn test_normalize_task_file() {
 
     // デフォルト設定でファイルを正規化
     let config = config::Config::default();
-    task::normalize_task_file(task_file_path, &config).unwrap();
+    assert!(task::normalize_task_file(task_file_path, &config).unwrap());
 
     // 正規化後の内容を検証
     let normalized = fs::read_to_string(task_file_path).unwrap();
@@ -87,6 +130,42 @@ fn test_normalize_task_file() {
         .iter()
         .any(|&l| l.contains("- [ ] 🟡 アーカイブ内の未完了タスク"));
     assert!(task_moved);
+
+    // 正規化済みのファイルは変更されない
+    assert_eq!(
+        task::normalize_contents(task_file_path, &normalized, &config).unwrap(),
+        normalized
+    );
+    assert!(!task::normalize_task_file(task_file_path, &config).unwrap());
+}
+
+#[test]
+fn test_normalize_rules_can_be_disabled() {
+    let content = "## タスク一覧\n\n- [ ] 優先度なしタスク\n\n\n- [ ] 🔴 別のグループ\n";
+
+    let mut config = config::Config::default();
+    config.normalize.default_priority = false;
+    config.normalize.collapse_blank_lines = false;
+    config.normalize.assign_ids = false;
+
+    // 無効にしたルールは適用されない
+    assert_eq!(
+        task::normalize_contents("tasks.md", content, &config).unwrap(),
+        content
+    );
+
+    // 有効なルールの名前だけが返される
+    let mut document = document::TaskDocument::parse(content);
+    config.normalize.default_priority = true;
+    assert_eq!(
+        normalize::normalize_document(&mut document, &config),
+        vec!["default_priority"]
+    );
+    assert!(
+        document
+            .to_string()
+            .contains("- [ ] 🟡 優先度なしタスク\n\n\n")
+    );
 }
 
 #[test]
@@ -98,6 +177,7 @@ fn test_archive_all_completed_tasks() {
     // テスト用のファイルパス
     let task_file = temp_path.join("archive-all-test.md");
     let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
 
     // 完了済みタスクと未完了タスクが混在するテストファイルを作成
     let content = r#"## タスク一覧
@@ -115,7 +195,7 @@ fn test_archive_all_completed_tasks() {
     fs::write(task_file_path, content).unwrap();
 
     // archive_all_completed_tasks関数を実行
-    task::archive_all_completed_tasks(task_file_path).unwrap();
+    task::archive_all_completed_tasks(task_file_path, &config).unwrap();
 
     // 結果を検証
     let result = fs::read_to_string(task_file_path).unwrap();
@@ -175,3 +255,1087 @@ fn test_archive_all_completed_tasks() {
             .any(|&l| l.contains("完了済みタスク3"))
     );
 }
+
+#[test]
+fn test_commands_share_task_numbers() {
+    // テスト用の一時ディレクトリを作成
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("numbering-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [ ] 🔴 タスク1
+- [x] 🟡 タスク2
+- [ ] 🟢 タスク3
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // 完了済みのタスクは完了にできず、未完了のタスクはアーカイブできない
+    assert!(
+        task::complete_tasks(task_file_path, &config, &TaskRef::Number(2).into(), false).is_err()
+    );
+    assert!(task::archive_tasks(task_file_path, &config, &TaskRef::Number(3).into()).is_err());
+
+    // 同じ番号が done と archive で同じタスクを指す
+    task::complete_tasks(task_file_path, &config, &TaskRef::Number(3).into(), false).unwrap();
+    task::archive_tasks(task_file_path, &config, &TaskRef::Number(3).into()).unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.contains("## アーカイブ\n\n- [x] 🟢 タスク3\n"));
+
+    // 存在しない番号の削除は失敗し、ファイルは変更されない
+    let error = task::remove_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Number(9).into(),
+        |_, _| true,
+    )
+    .unwrap_err();
+    assert!(matches!(&error, MdTaskError::TaskNotFound(task) if task == "9"));
+    assert_eq!(error.exit_code(), 3);
+    task::remove_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Number(1).into(),
+        |_, _| true,
+    )
+    .unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.contains("## タスク一覧\n\n- [x] 🟡 タスク2\n"));
+    assert!(result.contains("## ゴミ箱\n\n- [ ] 🔴 タスク1"));
+}
+
+#[test]
+fn test_task_ids_are_stable() {
+    // テスト用の一時ディレクトリを作成
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("id-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    // 追加時にIDが割り当てられる
+    let first_id = task::add_task_to_file(
+        task_file_path,
+        &config,
+        "最初のタスク",
+        "low",
+        &TaskOptions::default(),
+    )
+    .unwrap();
+    let second_id = task::add_task_to_file(
+        task_file_path,
+        &config,
+        "次のタスク",
+        "high",
+        &TaskOptions::default(),
+    )
+    .unwrap();
+    assert_ne!(first_id, second_id);
+    let content = fs::read_to_string(task_file_path).unwrap();
+    assert!(content.contains(&format!("- [ ] 🟢 最初のタスク <!-- id:{} -->", first_id)));
+
+    // 番号がずれてもIDで同じタスクを指定できる
+    task::remove_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Number(1).into(),
+        |_, _| true,
+    )
+    .unwrap();
+    let second = TaskRef::Id(second_id.clone());
+    task::complete_tasks(task_file_path, &config, &second.clone().into(), false).unwrap();
+    task::archive_tasks(task_file_path, &config, &second.clone().into()).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let location = document.find_task(&second).unwrap();
+    assert!(document.is_archive(location.section));
+
+    // 正規化でIDのないタスクと重複したIDにIDが補われ、既存のIDは変わらない
+    fs::write(
+        task_file_path,
+        "## タスク一覧\n\n- [ ] 🔴 A <!-- id:ab12 -->\n- [ ] B\n- [ ] 🟢 C <!-- id:ab12 -->\n",
+    )
+    .unwrap();
+    task::normalize_task_file(task_file_path, &config).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let ids: Vec<&str> = document
+        .tasks()
+        .iter()
+        .map(|location| document.task(location).id().unwrap())
+        .collect();
+    assert_eq!(ids[0], "ab12");
+    assert_ne!(ids[1], ids[2]);
+    assert_ne!(ids[2], "ab12");
+    assert_eq!(document.task(&document.tasks()[1]).body(), "🟡 B");
+}
+
+#[test]
+fn test_add_task_with_due_date_and_filter() {
+    // テスト用の一時ディレクトリを作成
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("due-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let due = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+    let options = TaskOptions {
+        due: Some(due),
+        ..Default::default()
+    };
+    task::add_task_to_file(task_file_path, &config, "期日付きタスク", "high", &options).unwrap();
+    task::add_task_to_file(
+        task_file_path,
+        &config,
+        "期日なしタスク",
+        "low",
+        &TaskOptions::default(),
+    )
+    .unwrap();
+
+    // 期日は本文の後ろ、IDコメントの前に書き込まれる
+    let content = fs::read_to_string(task_file_path).unwrap();
+    assert!(content.contains("- [ ] 🔴 期日付きタスク 📅 2024-12-31 <!-- id:"));
+
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let tasks = document.tasks();
+    let with_due = document.task(&tasks[0]);
+    let without_due = document.task(&tasks[1]);
+    assert_eq!(with_due.due(), Some(due));
+    assert_eq!(without_due.due(), None);
+
+    // 期日での絞り込み（期日のないタスクは対象外）
+    let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    let overdue = TaskFilter {
+        overdue: true,
+        ..Default::default()
+    };
+    assert!(overdue.matches(with_due, today));
+    assert!(!overdue.matches(without_due, today));
+    let due_before = TaskFilter {
+        due_before: Some(due),
+        ..Default::default()
+    };
+    assert!(!due_before.matches(with_due, today));
+    assert!(TaskFilter::default().matches(without_due, today));
+}
+
+#[test]
+fn test_tags() {
+    // テスト用の一時ディレクトリを作成
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("tag-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let options = TaskOptions {
+        tags: vec!["work".to_string(), "urgent".to_string()],
+        ..Default::default()
+    };
+    task::add_task_to_file(task_file_path, &config, "レビュー", "high", &options).unwrap();
+    fs::write(
+        task_file_path,
+        fs::read_to_string(task_file_path).unwrap() + "- [x] 資料作成 #work\n",
+    )
+    .unwrap();
+
+    // 正規化してもタグは保持される
+    task::normalize_task_file(task_file_path, &config).unwrap();
+    let content = fs::read_to_string(task_file_path).unwrap();
+    assert!(content.contains("- [ ] 🔴 レビュー #work #urgent <!-- id:"));
+    assert!(content.contains("- [x] 🟡 資料作成 #work <!-- id:"));
+
+    // タグでの絞り込み（複数指定は全てを満たすもの、大文字小文字は区別しない）
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let tasks = document.tasks();
+    let filter = TaskFilter {
+        tags: vec!["Work".to_string(), "#urgent".to_string()],
+        ..Default::default()
+    };
+    let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+    assert!(filter.matches(document.task(&tasks[0]), today));
+    assert!(!filter.matches(document.task(&tasks[1]), today));
+
+    // タグごとの未完了・完了済みの件数
+    let counts = task::count_tags([&document]);
+    assert_eq!(counts.get("work"), Some(&(1, 1)));
+    assert_eq!(counts.get("urgent"), Some(&(1, 0)));
+
+    // アーカイブファイルのタスクも完了済みとして数える
+    let archive = TaskDocument::parse("## アーカイブ\n- [x] 🟡 古いタスク #work #w\n");
+    let counts = task::count_tags([&document, &archive]);
+    assert_eq!(counts.get("work"), Some(&(1, 2)));
+    assert_eq!(counts.get("w"), Some(&(0, 1)));
+}
+
+#[test]
+fn test_subtasks() {
+    // テスト用の一時ディレクトリを作成
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("subtask-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [x] 🔴 親タスク
+  - [ ] 🟡 子タスク1
+    メモ
+  - [x] 🟡 子タスク2
+- [ ] 🟢 別のタスク
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // サブタスクとして追加すると親の末尾に同じインデントで入る
+    let options = TaskOptions {
+        parent: Some(TaskRef::Number(1)),
+        ..Default::default()
+    };
+    task::add_task_to_file(task_file_path, &config, "子タスク3", "low", &options).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let tasks = document.tasks();
+    assert_eq!(tasks[3].parent, Some(1));
+    assert_eq!(tasks[3].depth, 1);
+    assert!(document.task(&tasks[3]).content.starts_with("🟢 子タスク3"));
+    assert_eq!(document.progress(&tasks[0]), (1, 3));
+
+    // 存在しない親を指定するとエラー
+    let options = TaskOptions {
+        parent: Some(TaskRef::Number(9)),
+        ..Default::default()
+    };
+    assert!(matches!(
+        task::add_task_to_file(task_file_path, &config, "孤児", "low", &options),
+        Err(MdTaskError::ParentNotFound(_))
+    ));
+
+    // サブタスクは単独ではアーカイブできず、親と一緒に移動する
+    assert!(task::archive_tasks(task_file_path, &config, &TaskRef::Number(3).into()).is_err());
+    task::archive_all_completed_tasks(task_file_path, &config).unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.starts_with(
+        "## タスク一覧\n\n- [ ] 🟢 別のタスク\n\n## アーカイブ\n\n- [x] 🔴 親タスク\n"
+    ));
+    assert!(
+        result
+            .contains("  - [ ] 🟡 子タスク1\n    メモ\n  - [x] 🟡 子タスク2\n  - [ ] 🟢 子タスク3")
+    );
+
+    // 正規化でアーカイブ内の未完了のサブタスクが親から引き離されない
+    task::normalize_task_file(task_file_path, &config).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let parent = document.tasks()[1];
+    assert!(document.is_archive(parent.section));
+    assert_eq!(document.progress(&parent), (1, 3));
+
+    // 親を完了にするとき、cascade を指定するとサブタスクもまとめて完了になる
+    fs::write(task_file_path, content.replace("- [x] 🔴", "- [ ] 🔴")).unwrap();
+    task::complete_tasks(task_file_path, &config, &TaskRef::Number(1).into(), true).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    assert!(
+        document
+            .tasks()
+            .iter()
+            .take(3)
+            .all(|t| document.task(t).state == TaskState::Done)
+    );
+
+    // 親を削除するとサブタスクも削除される
+    let mut delete = config::Config::default();
+    delete.trash.mode = config::TrashMode::Delete;
+    task::remove_tasks(
+        task_file_path,
+        &delete,
+        &TaskRef::Number(1).into(),
+        |_, _| true,
+    )
+    .unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert_eq!(result, "## タスク一覧\n\n- [ ] 🟢 別のタスク\n");
+}
+
+#[test]
+fn test_configured_headings() {
+    // テスト用の一時ディレクトリを作成
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("heading-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+
+    let mut config = config::Config::default();
+    config.sections.task_heading = "Tasks".to_string();
+    config.sections.archive_heading = "Archive".to_string();
+    config.sections.heading_level = 3;
+
+    let content = r#"## タスク一覧
+
+- [x] 🔴 Done task
+- [ ] 🟡 Open task
+
+## アーカイブ
+
+- [x] 🟢 Old task
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // 既存の見出しを設定の見出しに置き換える
+    let renamed =
+        task::migrate_headings(task_file_path, &config, "タスク一覧", "アーカイブ").unwrap();
+    assert_eq!(renamed, 2);
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.starts_with("### Tasks\n"));
+    assert!(result.contains("\n### Archive\n"));
+
+    // 設定の見出しに対してアーカイブ・追加・正規化が行われる
+    task::archive_all_completed_tasks(task_file_path, &config).unwrap();
+    task::add_task_to_file(
+        task_file_path,
+        &config,
+        "New task",
+        "low",
+        &TaskOptions::default(),
+    )
+    .unwrap();
+    task::normalize_task_file(task_file_path, &config).unwrap();
+    let document = task::load_document(task_file_path, &config).unwrap();
+    assert_eq!(document.sections.len(), 2);
+    let tasks = document.tasks();
+    assert!(document.task(&tasks[1]).content.starts_with("🟢 New task"));
+    assert!(document.is_archive(tasks[2].section));
+    assert!(document.task(&tasks[2]).content.starts_with("🔴 Done task"));
+}
+
+#[test]
+fn test_sort_tasks() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("sort-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [ ] 🟢 beta 📅 2026-11-01
+  - [ ] 🔴 子タスク
+    メモ
+- [ ] 🔴 Alpha
+- [ ] 🟡 gamma 📅 2026-10-20
+- [ ] 🔴 delta 📅 2026-10-01
+
+## アーカイブ
+- [x] 🟢 archived
+"#;
+    fs::write(task_file_path, content).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let texts = |order: Vec<document::TaskLocation>| -> Vec<String> {
+        order
+            .iter()
+            .map(|location| document.task(location).text().to_string())
+            .collect()
+    };
+
+    // 優先度が同じタスクは元の順序を保ち、サブタスクは親の直後に続く
+    assert_eq!(
+        texts(sort::sorted_tasks(
+            &document,
+            sort::SortKey::Priority,
+            false
+        )),
+        [
+            "Alpha",
+            "delta 📅 2026-10-01",
+            "gamma 📅 2026-10-20",
+            "beta 📅 2026-11-01",
+            "子タスク",
+            "archived"
+        ]
+    );
+    // 期日のないタスクは逆順でも後ろ
+    assert_eq!(
+        texts(sort::sorted_tasks(&document, sort::SortKey::Due, true)),
+        [
+            "beta 📅 2026-11-01",
+            "子タスク",
+            "gamma 📅 2026-10-20",
+            "delta 📅 2026-10-01",
+            "Alpha",
+            "archived"
+        ]
+    );
+    assert_eq!(
+        texts(sort::sorted_tasks(&document, sort::SortKey::Alpha, false))[..3],
+        ["Alpha", "beta 📅 2026-11-01", "子タスク"]
+    );
+
+    // 並べ替えの基準を指定しない逆順は、作成日に関係なくファイル内の順番の逆になる
+    let dated = TaskDocument::parse(
+        "- [ ] 🟡 first ➕ 2026-10-10\n- [ ] 🟡 second ➕ 2026-10-01\n  - [ ] 🟡 sub\n- [ ] 🟡 third ➕ 2026-10-05\n- [ ] 🟡 fourth\n",
+    );
+    let reversed: Vec<&str> = sort::reversed_tasks(&dated)
+        .iter()
+        .map(|location| dated.task(location).text())
+        .collect();
+    assert_eq!(
+        reversed,
+        [
+            "fourth",
+            "third ➕ 2026-10-05",
+            "second ➕ 2026-10-01",
+            "sub",
+            "first ➕ 2026-10-10"
+        ]
+    );
+
+    // 作成日順では作成日のあるタスクが先で、作成日のないタスクはファイル内の順番で後ろに続く
+    let created = TaskDocument::parse(
+        "- [ ] 🟡 b\n- [ ] 🟡 c ➕ 2026-10-02\n- [ ] 🟡 a\n- [ ] 🟡 d ➕ 2026-10-01\n",
+    );
+    let created_texts = |reverse: bool| -> Vec<String> {
+        sort::sorted_tasks(&created, sort::SortKey::Created, reverse)
+            .iter()
+            .map(|location| created.task(location).text().to_string())
+            .collect()
+    };
+    assert_eq!(
+        created_texts(false),
+        ["d ➕ 2026-10-01", "c ➕ 2026-10-02", "b", "a"]
+    );
+    assert_eq!(
+        created_texts(true),
+        ["c ➕ 2026-10-02", "d ➕ 2026-10-01", "a", "b"]
+    );
+
+    // ファイル上の並べ替えはタスク一覧セクションだけが対象で、サブタスクとメモも一緒に移動する
+    assert_eq!(
+        task::sort_task_file(task_file_path, &config, sort::SortKey::Priority, false).unwrap(),
+        4
+    );
+    assert_eq!(
+        fs::read_to_string(task_file_path).unwrap(),
+        r#"## タスク一覧
+
+- [ ] 🔴 Alpha
+- [ ] 🔴 delta 📅 2026-10-01
+- [ ] 🟡 gamma 📅 2026-10-20
+- [ ] 🟢 beta 📅 2026-11-01
+  - [ ] 🔴 子タスク
+    メモ
+
+## アーカイブ
+- [x] 🟢 archived
+"#
+    );
+}
+
+#[test]
+fn test_bulk_operations() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("bulk-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [ ] 🔴 タスク1 #obsolete
+  - [ ] 🟡 サブタスク2 #obsolete
+- [ ] 🟡 タスク3
+- [ ] 🟢 タスク4
+- [ ] 🟢 タスク5 #obsolete
+- [ ] 🟢 タスク6
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // 番号と範囲は実行前のファイルに対して解決される
+    let targets = task::TaskTargets {
+        selectors: vec!["3".parse().unwrap(), "5-6".parse().unwrap()],
+        ..Default::default()
+    };
+    assert!(targets.is_bulk());
+    assert_eq!(
+        task::complete_tasks(task_file_path, &config, &targets, false).unwrap(),
+        [3, 5, 6]
+    );
+
+    // 個別に指定したタスクが1つでも対象にならなければ何も変更しない
+    let before = fs::read_to_string(task_file_path).unwrap();
+    let targets = task::TaskTargets {
+        selectors: vec!["1".parse().unwrap(), "3".parse().unwrap()],
+        ..Default::default()
+    };
+    assert!(matches!(
+        task::complete_tasks(task_file_path, &config, &targets, false),
+        Err(MdTaskError::TaskNotFound(task)) if task == "3"
+    ));
+    assert_eq!(fs::read_to_string(task_file_path).unwrap(), before);
+
+    // 最後のタスクより後ろの番号を含む範囲は、範囲を展開せずにエラーにする
+    let targets = task::TaskTargets {
+        selectors: vec!["4-9999999999".parse().unwrap()],
+        ..Default::default()
+    };
+    assert!(matches!(
+        task::complete_tasks(task_file_path, &config, &targets, false),
+        Err(MdTaskError::TaskNotFound(task)) if task == "7"
+    ));
+    assert_eq!(fs::read_to_string(task_file_path).unwrap(), before);
+
+    // 条件に一致するタスクを削除する。確認で取りやめると変更しない
+    let filter = "#obsolete";
+    let targets = task::TaskTargets {
+        filter: Some(
+            TaskFilter::parse(filter, NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()).unwrap(),
+        ),
+        filter_expr: filter.to_string(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        task::remove_tasks(task_file_path, &config, &targets, |_, _| false),
+        Err(MdTaskError::Aborted)
+    ));
+    let removed = task::remove_tasks(task_file_path, &config, &targets, |_, locations| {
+        locations.len() == 3
+    })
+    .unwrap();
+    assert_eq!(removed, [1, 2, 5]);
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let remaining: Vec<&str> = document
+        .tasks()
+        .iter()
+        .map(|location| document.task(location).text())
+        .collect();
+    assert_eq!(remaining, ["タスク3", "タスク4", "タスク6"]);
+    assert_eq!(document.trashed_tasks().len(), 3);
+
+    // 一致するタスクがなければエラー
+    assert!(matches!(
+        task::remove_tasks(task_file_path, &config, &targets, |_, _| true),
+        Err(MdTaskError::NoMatchingTasks(expr)) if expr == "#obsolete"
+    ));
+
+    // 条件式は優先度・状態・本文の語を組み合わせられる
+    let today = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+    let filter = TaskFilter::parse("priority:low done タスク", today).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let matched: Vec<usize> = document
+        .tasks()
+        .iter()
+        .filter(|location| filter.matches(document.task(location), today))
+        .map(|location| location.number)
+        .collect();
+    assert_eq!(matched, [3]);
+    assert!(matches!(
+        TaskFilter::parse("priority:urgent", today),
+        Err(MdTaskError::InvalidPriority(_))
+    ));
+}
+
+#[test]
+fn test_reopen_tasks() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("reopen-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [ ] 🔴 高
+- [x] 🟡 完了済み
+- [ ] 🟢 低
+
+## アーカイブ
+
+- [x] 🟡 アーカイブ済み <!-- id:ab12 -->
+  - [x] 🟢 サブタスク
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // 未完了のタスクとアーカイブ内のサブタスクは単独では再開できない
+    let open: task::TaskTargets = TaskRef::Number(1).into();
+    assert!(task::reopen_tasks(task_file_path, &config, &open).is_err());
+    let subtask: task::TaskTargets = TaskRef::Number(5).into();
+    assert!(task::reopen_tasks(task_file_path, &config, &subtask).is_err());
+
+    // アーカイブ内のタスクはサブタスクごと同じ優先度のタスクの後ろに戻り、戻した後の番号を返す
+    let targets = task::TaskTargets {
+        selectors: vec!["2".parse().unwrap(), "ab12".parse().unwrap()],
+        ..Default::default()
+    };
+    assert_eq!(
+        task::reopen_tasks(task_file_path, &config, &targets).unwrap(),
+        [2, 3]
+    );
+    let result = fs::read_to_string(task_file_path).unwrap();
+    let today = chrono::Local::now().date_naive().format("%Y-%m-%d");
+    assert_eq!(
+        result,
+        format!(
+            r#"## タスク一覧
+
+- [ ] 🔴 高
+- [ ] 🟡 完了済み <!-- reopened:{today} -->
+- [ ] 🟡 アーカイブ済み <!-- id:ab12 reopened:{today} -->
+  - [x] 🟢 サブタスク
+- [ ] 🟢 低
+
+## アーカイブ
+
+"#
+        )
+    );
+
+    // IDのないタスクも移動した後の番号を返す
+    fs::write(
+        task_file_path,
+        "## タスク一覧\n\n- [ ] 🟡 中\n\n## アーカイブ\n\n- [x] 🔴 高\n",
+    )
+    .unwrap();
+    assert_eq!(
+        task::reopen_tasks(task_file_path, &config, &TaskRef::Number(2).into()).unwrap(),
+        [1]
+    );
+}
+
+#[test]
+fn test_task_states() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("state-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [ ] 🔴 A
+- [/] 🟡 B
+- [ ] 🟢 C
+  - [ ] 🟢 C-1
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // すでにその状態のタスクは対象にならない
+    let started: task::TaskTargets = TaskRef::Number(2).into();
+    assert!(
+        task::set_task_state(task_file_path, &config, &started, TaskState::InProgress).is_err()
+    );
+
+    let targets: task::TaskTargets = TaskRef::Number(1).into();
+    task::set_task_state(task_file_path, &config, &targets, TaskState::Waiting).unwrap();
+    let targets: task::TaskTargets = TaskRef::Number(3).into();
+    task::set_task_state(task_file_path, &config, &targets, TaskState::Cancelled).unwrap();
+
+    // 中止したタスクは完了にできず、状態で絞り込める
+    assert!(task::complete_tasks(task_file_path, &config, &targets, false).is_err());
+    let today = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+    let filter = TaskFilter::parse("waiting", today).unwrap();
+    assert_eq!(filter.state, Some(TaskState::Waiting));
+
+    // 中止したタスクも完了済みと同様にアーカイブされる
+    task::archive_all_completed_tasks(task_file_path, &config).unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert_eq!(
+        result,
+        r#"## タスク一覧
+
+- [?] 🔴 A
+- [/] 🟡 B
+
+## アーカイブ
+
+- [-] 🟢 C
+  - [ ] 🟢 C-1
+"#
+    );
+}
+
+#[test]
+fn test_add_duplicate_policy() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("duplicate-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let mut config = config::Config::default();
+    let options = TaskOptions::default();
+
+    fs::write(task_file_path, "## タスク一覧\n\n- [ ] 🟡 会議の準備\n").unwrap();
+
+    // 優先度や空白が違っても同じ本文なら追加しない
+    config.task_management.on_duplicate = config::DuplicatePolicy::Refuse;
+    let result = task::add_task_to_file(task_file_path, &config, "会議の 準備", "high", &options);
+    assert!(matches!(result, Err(MdTaskError::DuplicateTask(1))));
+
+    // 警告する設定（デフォルト）では追加する
+    config.task_management.on_duplicate = config::DuplicatePolicy::Warn;
+    task::add_task_to_file(task_file_path, &config, "会議の準備", "high", &options).unwrap();
+
+    let removed = task::dedupe_task_file(task_file_path, &config, |_, _| true).unwrap();
+    assert_eq!(removed, (1, 1));
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert_eq!(result.matches("会議の準備").count(), 1);
+    assert!(result.contains("🔴 会議の準備"));
+}
+
+#[test]
+fn test_edit_and_reprioritize() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("edit-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = "## タスク一覧\n\n- [ ] 🟡 会議の準備\n- [/] 🟢 資料 <!-- id:ab12 -->\n";
+    fs::write(task_file_path, content).unwrap();
+
+    // 状態・優先度記号・IDは本文を変えても残る
+    let id = TaskRef::Id("ab12".to_string());
+    task::edit_task_text(task_file_path, &config, &id, "資料の作成 #work").unwrap();
+    task::set_task_priority(task_file_path, &config, &id, "high").unwrap();
+    task::set_task_priority(task_file_path, &config, &TaskRef::Number(1), "low").unwrap();
+    assert!(task::set_task_priority(task_file_path, &config, &id, "urgent").is_err());
+    assert!(task::edit_task_text(task_file_path, &config, &TaskRef::Number(9), "x").is_err());
+
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert_eq!(
+        result,
+        "## タスク一覧\n\n- [ ] 🟢 会議の準備\n- [/] 🔴 資料の作成 #work <!-- id:ab12 -->\n"
+    );
+
+    // エディタにはタスクの行番号とファイルを渡し、編集後に読み込めない行を返す
+    let script = temp_dir.path().join("editor.sh");
+    fs::write(
+        &script,
+        "echo \"$1\" > \"$2.args\"\necho '- [>] 壊れた行' >> \"$2\"\n",
+    )
+    .unwrap();
+    let editor = format!("sh {}", script.display());
+    let malformed = task::edit_in_editor(task_file_path, &config, &id, &editor).unwrap();
+    assert_eq!(malformed, [5]);
+    let args = fs::read_to_string(format!("{}.args", task_file_path)).unwrap();
+    assert_eq!(args.trim(), "+4");
+
+    // エディタが失敗した場合はエラー
+    let failed = task::edit_in_editor(task_file_path, &config, &id, "false");
+    assert!(matches!(failed, Err(MdTaskError::EditorFailed(_))));
+}
+
+#[test]
+fn test_trash() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("trash-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let mut config = config::Config::default();
+    let today = date::today().format("%Y-%m-%d").to_string();
+
+    let content = r#"## タスク一覧
+
+- [ ] 🟢 タスク1 <!-- id:aa11 -->
+  - [ ] 🟡 サブタスク2
+- [ ] 🔴 タスク3
+
+## アーカイブ
+
+- [x] 🟡 古いタスク
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // 削除したタスクは削除日を付けてゴミ箱に移り、番号の対象から外れる。サブタスクは最上位になる
+    let targets = task::TaskTargets {
+        selectors: vec!["1".parse().unwrap(), "2".parse().unwrap()],
+        ..Default::default()
+    };
+    task::remove_tasks(task_file_path, &config, &targets, |_, _| true).unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert_eq!(
+        result,
+        format!(
+            "## タスク一覧\n\n- [ ] 🔴 タスク3\n\n## アーカイブ\n\n- [x] 🟡 古いタスク\n\n## ゴミ箱\n\n\
+             - [ ] 🟢 タスク1 <!-- id:aa11 removed:{today} -->\n  - [ ] 🟡 サブタスク2\n"
+        )
+    );
+    let document = TaskDocument::load(task_file_path).unwrap();
+    assert_eq!(document.tasks().len(), 2);
+
+    // IDで戻すと削除日が消え、優先度の位置に入る
+    task::restore_task(task_file_path, &config, &TaskRef::Id("aa11".to_string())).unwrap();
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.starts_with(
+        "## タスク一覧\n\n- [ ] 🔴 タスク3\n- [ ] 🟢 タスク1 <!-- id:aa11 -->\n  - [ ] 🟡 サブタスク2\n"
+    ));
+    assert!(matches!(
+        task::restore_task(task_file_path, &config, &TaskRef::Number(1)),
+        Err(MdTaskError::TaskNotFound(_))
+    ));
+
+    // 期間を指定すると、それより古いタスク（削除日のないものを含む）だけを完全に削除する
+    task::remove_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Number(1).into(),
+        |_, _| true,
+    )
+    .unwrap();
+    let mut document = TaskDocument::load(task_file_path).unwrap();
+    document.push_trashed(vec![document::Node::Task(
+        document::TaskItem::parse("- [ ] 🟡 古い削除 <!-- removed:2000-01-01 -->").unwrap(),
+    )]);
+    document.save(task_file_path).unwrap();
+    // 日付で表せないほど長い期間を指定した場合は何も削除しない
+    assert_eq!(
+        task::empty_trash(task_file_path, &config, Some(u64::MAX)).unwrap(),
+        0
+    );
+    assert_eq!(
+        task::empty_trash(task_file_path, &config, Some(30)).unwrap(),
+        1
+    );
+    assert_eq!(task::empty_trash(task_file_path, &config, None).unwrap(), 1);
+    let document = TaskDocument::load(task_file_path).unwrap();
+    assert!(document.trashed_tasks().is_empty());
+
+    // file モードでは別のファイルに移し、そこから戻す
+    config.trash.mode = config::TrashMode::File;
+    let trash_path = task::trash_file_path(task_file_path, &config);
+    assert!(trash_path.ends_with("trash-test.trash.md"));
+    // ゴミ箱ファイルは操作履歴に記録する対象になる
+    assert_eq!(
+        task::related_files(task_file_path, &config).unwrap(),
+        [trash_path.as_str()]
+    );
+    task::remove_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Number(1).into(),
+        |_, _| true,
+    )
+    .unwrap();
+    assert!(
+        !fs::read_to_string(task_file_path)
+            .unwrap()
+            .contains("タスク1")
+    );
+    assert_eq!(
+        fs::read_to_string(&trash_path).unwrap(),
+        format!(
+            "## ゴミ箱\n\n- [ ] 🟢 タスク1 <!-- id:aa11 removed:{today} -->\n  - [ ] 🟡 サブタスク2\n"
+        )
+    );
+    task::restore_task(task_file_path, &config, &TaskRef::Number(1)).unwrap();
+    assert!(
+        fs::read_to_string(task_file_path)
+            .unwrap()
+            .contains("🟢 タスク1 <!-- id:aa11 -->")
+    );
+    assert_eq!(fs::read_to_string(&trash_path).unwrap(), "## ゴミ箱\n\n");
+}
+
+#[test]
+fn test_created_and_completed_dates() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("dates-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let mut config = config::Config::default();
+    let today = date::today().format("%Y-%m-%d").to_string();
+
+    // デフォルトでは日付を記録しない
+    let options = TaskOptions::default();
+    task::add_task_to_file(task_file_path, &config, "記録なし", "low", &options).unwrap();
+    task::complete_tasks(task_file_path, &config, &TaskRef::Number(1).into(), false).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let task = document.task(&document.tasks()[0]);
+    assert_eq!(task.text(), "記録なし");
+
+    // 作成日は期日より前に入る
+    config.task_management.record_created = true;
+    config.task_management.record_completed = true;
+    let options = TaskOptions {
+        due: NaiveDate::from_ymd_opt(2026, 12, 31),
+        ..Default::default()
+    };
+    task::add_task_to_file(task_file_path, &config, "親", "high", &options).unwrap();
+    let options = TaskOptions {
+        parent: Some(TaskRef::Number(2)),
+        ..Default::default()
+    };
+    task::add_task_to_file(task_file_path, &config, "子", "low", &options).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let parent = document.task(&document.tasks()[1]);
+    assert_eq!(parent.text(), format!("親 ➕ {today} 📅 2026-12-31"));
+
+    // cascade で完了にしたサブタスクにも完了日が入り、再開すると取り除かれる
+    task::complete_tasks(task_file_path, &config, &TaskRef::Number(2).into(), true).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    for location in &document.tasks()[1..] {
+        assert_eq!(document.task(location).completed(), Some(date::today()));
+    }
+    task::reopen_tasks(task_file_path, &config, &TaskRef::Number(2).into()).unwrap();
+    let document = TaskDocument::load(task_file_path).unwrap();
+    let parent = document.task(&document.tasks()[1]);
+    assert_eq!(parent.completed(), None);
+    assert_eq!(parent.created(), Some(date::today()));
+
+    // 作成日の違いは重複の判定に影響しない
+    config.task_management.on_duplicate = config::DuplicatePolicy::Refuse;
+    let options = TaskOptions {
+        due: NaiveDate::from_ymd_opt(2026, 12, 31),
+        ..Default::default()
+    };
+    assert!(matches!(
+        task::add_task_to_file(task_file_path, &config, "親", "high", &options),
+        Err(MdTaskError::DuplicateTask(2))
+    ));
+}
+
+#[test]
+fn test_archive_to_files() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("tasks.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let mut config = config::Config::default();
+    config.archive.target = config::ArchiveTarget::Monthly;
+
+    let content = r#"## タスク一覧
+
+- [x] 🟡 9月に完了 ✅ 2026-09-30 <!-- id:aa11 -->
+  - [x] 🟢 サブタスク
+- [ ] 🟢 未完了
+- [-] 🔴 完了日なし <!-- id:cc33 -->
+"#;
+    fs::write(task_file_path, content).unwrap();
+
+    // 月ごとのファイルは完了日（なければ今日）で選ぶ
+    task::archive_all_completed_tasks(task_file_path, &config).unwrap();
+    assert_eq!(
+        fs::read_to_string(task_file_path).unwrap(),
+        "## タスク一覧\n\n- [ ] 🟢 未完了\n"
+    );
+    let september = temp_dir.path().join("archive/2026-09.md");
+    assert_eq!(
+        fs::read_to_string(&september).unwrap(),
+        "## アーカイブ\n\n- [x] 🟡 9月に完了 ✅ 2026-09-30 <!-- id:aa11 -->\n  - [x] 🟢 サブタスク\n"
+    );
+    let this_month = task::archive_file_path(task_file_path, &config, date::today());
+    assert!(
+        fs::read_to_string(&this_month)
+            .unwrap()
+            .contains("- [-] 🔴 完了日なし <!-- id:cc33 -->")
+    );
+
+    // アーカイブファイルは新しい順に読み込める
+    let archives = task::load_archive_documents(task_file_path, &config).unwrap();
+    let paths: Vec<&str> = archives.iter().map(|(path, _)| path.as_str()).collect();
+    if this_month != september.to_str().unwrap() {
+        assert_eq!(paths, [this_month.as_str(), september.to_str().unwrap()]);
+    }
+    assert_eq!(archives.last().unwrap().1.tasks().len(), 2);
+
+    // 1つのファイルにまとめる場合は新しいタスクを先頭に追加する
+    config.archive.target = config::ArchiveTarget::File;
+    fs::write(task_file_path, content).unwrap();
+    task::archive_tasks(task_file_path, &config, &TaskRef::Number(1).into()).unwrap();
+    task::archive_tasks(task_file_path, &config, &TaskRef::Number(2).into()).unwrap();
+    let archive_file = temp_dir.path().join("archive.md");
+    let archived = fs::read_to_string(&archive_file).unwrap();
+    assert!(archived.starts_with("## アーカイブ\n\n- [-] 🔴 完了日なし"));
+    assert!(archived.contains("\n- [x] 🟡 9月に完了"));
+    let archives = task::load_archive_documents(task_file_path, &config).unwrap();
+    assert_eq!(archives.len(), 1);
+
+    // アーカイブファイルのIDは新しいタスクに使わない
+    let reserved = task::reserved_ids(task_file_path, &config).unwrap();
+    assert!(reserved.contains("aa11") && reserved.contains("cc33"));
+
+    // アーカイブファイルは操作履歴に記録する対象になる
+    assert_eq!(
+        task::related_files(task_file_path, &config).unwrap(),
+        [archive_file.to_str().unwrap()]
+    );
+    assert!(
+        archives[0]
+            .1
+            .tasks()
+            .iter()
+            .all(|l| archives[0].1.is_archive(l.section))
+    );
+
+    // アーカイブファイルのタスクもIDで再開でき、サブタスクごとタスク一覧に戻る
+    let reopened = task::reopen_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Id("aa11".to_string()).into(),
+    )
+    .unwrap();
+    assert_eq!(reopened, [1]);
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.contains("- [ ] 🟡 9月に完了 <!-- id:aa11 reopened:"));
+    assert!(result.contains("  - [x] 🟢 サブタスク\n"));
+    let archived = fs::read_to_string(&archive_file).unwrap();
+    assert!(!archived.contains("9月に完了"));
+    assert!(archived.contains("完了日なし"));
+
+    // 月ごとのファイルからも再開できる。見つからないIDはエラー
+    config.archive.target = config::ArchiveTarget::Monthly;
+    task::reopen_tasks(
+        task_file_path,
+        &config,
+        &TaskRef::Id("cc33".to_string()).into(),
+    )
+    .unwrap();
+    assert!(!fs::read_to_string(&this_month).unwrap().contains("cc33"));
+    assert!(
+        fs::read_to_string(task_file_path)
+            .unwrap()
+            .contains("- [ ] 🔴 完了日なし <!-- id:cc33 reopened:")
+    );
+    assert!(matches!(
+        task::reopen_tasks(
+            task_file_path,
+            &config,
+            &TaskRef::Id("ffff".to_string()).into()
+        ),
+        Err(MdTaskError::TaskNotFound(_))
+    ));
+
+    // セクションの場合はアーカイブファイルを読み込まない
+    config.archive.target = config::ArchiveTarget::Section;
+    assert!(
+        task::load_archive_documents(task_file_path, &config)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_ranges_skip_non_targets() {
+    let temp_dir = tempdir().unwrap();
+    let task_file = temp_dir.path().join("range-test.md");
+    let task_file_path = task_file.to_str().unwrap();
+    let config = config::Config::default();
+
+    let content = r#"## タスク一覧
+
+- [x] 🔴 親タスク
+  - [x] 🟡 サブタスク
+- [ ] 🟡 タスク3
+- [x] 🟢 タスク4
+- [ ] 🟢 タスク5
+"#;
+    fs::write(task_file_path, content).unwrap();
+    let range = |range: &str| task::TaskTargets {
+        selectors: vec![range.parse().unwrap()],
+        ..Default::default()
+    };
+
+    // 範囲内の完了済みのタスクは飛ばす
+    assert_eq!(
+        task::complete_tasks(task_file_path, &config, &range("3-5"), false).unwrap(),
+        [3, 5]
+    );
+
+    // サブタスクは単独ではアーカイブできないが、親と一緒にアーカイブされる
+    assert_eq!(
+        task::archive_tasks(task_file_path, &config, &range("1-3")).unwrap(),
+        [1, 3]
+    );
+    let result = fs::read_to_string(task_file_path).unwrap();
+    assert!(result.starts_with("## タスク一覧\n\n- [x] 🟢 タスク4\n- [x] 🟢 タスク5\n"));
+    assert!(result.contains("- [x] 🔴 親タスク\n  - [x] 🟡 サブタスク\n"));
+
+    // 範囲内に対象が1つもなければエラー
+    assert!(matches!(
+        task::complete_tasks(task_file_path, &config, &range("1-2"), false),
+        Err(MdTaskError::TaskNotFound(task)) if task == "1-2"
+    ));
+}